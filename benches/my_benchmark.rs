@@ -1,3 +1,5 @@
+#![allow(clippy::iter_skip_next, clippy::unit_arg)]
+
 extern crate riffu;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
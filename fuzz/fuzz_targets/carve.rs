@@ -0,0 +1,18 @@
+//! Scans the input for embedded containers, from memory and through a reader.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use riffu::carve::{scan, scan_reader};
+
+fuzz_target!(|data: &[u8]| {
+    let found: Vec<_> = scan(data).map(|found| found.offset).collect();
+    let read: Vec<_> = scan_reader(data)
+        .map(|found| found.unwrap().offset)
+        .collect();
+    assert_eq!(found, read);
+    // A small limit exercises the truncation of oversized candidates.
+    for found in scan_reader(data).with_max_len(64) {
+        let _ = found.unwrap();
+    }
+});
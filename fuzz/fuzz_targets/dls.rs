@@ -0,0 +1,23 @@
+//! Parses the input as a DLS collection and resolves the wave of every region.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use riffu::{dls::Dls, Chunk, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+        let chunk = match Chunk::from_bytes_with_options(data, options) {
+            Ok(chunk) => chunk,
+            Err(_) => continue,
+        };
+        if let Ok(dls) = Dls::from_chunk(&chunk) {
+            for instrument in &dls.instruments {
+                let _ = instrument.is_drum();
+                for region in &instrument.regions {
+                    let _ = dls.wave(region);
+                }
+            }
+        }
+    }
+});
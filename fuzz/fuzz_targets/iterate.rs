@@ -0,0 +1,31 @@
+//! Iterates over the input as chunks laid out back to back, descending into containers.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use riffu::{error::RiffResult, Chunk, ChunkIter, ChunkKind, ParseOptions};
+
+fn descend(chunk: &Chunk<'_>) -> RiffResult<()> {
+    let _ = chunk.content();
+    let _ = chunk.siblings();
+    if chunk.kind() == ChunkKind::Raw {
+        return Ok(());
+    }
+    for child in chunk.iter()? {
+        descend(&child?)?;
+    }
+    Ok(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+        for chunk in ChunkIter::from_bytes_with_options(data, options) {
+            match chunk {
+                Ok(chunk) => {
+                    let _ = descend(&chunk);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+});
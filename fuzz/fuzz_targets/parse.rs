@@ -0,0 +1,27 @@
+//! Parses the input as the root chunk of a file and walks everything below it.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use riffu::{validate, Chunk, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+        let chunk = match Chunk::from_bytes_with_options(data, options) {
+            Ok(chunk) => chunk,
+            Err(_) => continue,
+        };
+        for entry in chunk.walk() {
+            let chunk = match entry {
+                Ok(entry) => entry.chunk,
+                Err(_) => break,
+            };
+            let _ = chunk.id();
+            let _ = chunk.chunk_type();
+            let _ = chunk.content();
+            let _ = chunk.path();
+        }
+        let _ = chunk.select("**");
+    }
+    let _ = validate::validate(data);
+});
@@ -0,0 +1,12 @@
+//! Repairs the input as a damaged file and writes the rebuilt tree out.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use riffu::repair::repair;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(repaired) = repair(data) {
+        let _ = repaired.contents.to_bytes();
+    }
+});
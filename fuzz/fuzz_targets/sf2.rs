@@ -0,0 +1,20 @@
+//! Parses the input as a SoundFont bank and writes it back out.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use riffu::{sf2::SoundFont, Chunk, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+        let chunk = match Chunk::from_bytes_with_options(data, options) {
+            Ok(chunk) => chunk,
+            Err(_) => continue,
+        };
+        if let Ok(bank) = SoundFont::from_chunk(&chunk) {
+            if let Ok(contents) = bank.to_contents() {
+                let _ = contents.to_bytes();
+            }
+        }
+    }
+});
@@ -0,0 +1,28 @@
+//! Parses the input as a WebP and rewrites its metadata, both kept and stripped.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use riffu::{
+    webp::{rewrite_metadata, Metadata, WebP},
+    Chunk, ParseOptions,
+};
+
+fuzz_target!(|data: &[u8]| {
+    for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+        let chunk = match Chunk::from_bytes_with_options(data, options) {
+            Ok(chunk) => chunk,
+            Err(_) => continue,
+        };
+        let webp = match WebP::from_chunk(&chunk) {
+            Ok(webp) => webp,
+            Err(_) => continue,
+        };
+        let _ = (webp.width(), webp.height(), webp.is_lossless());
+        for metadata in [webp.metadata(), Metadata::default()] {
+            if let Ok(contents) = rewrite_metadata(&chunk, &metadata) {
+                let _ = contents.to_bytes();
+            }
+        }
+    }
+});
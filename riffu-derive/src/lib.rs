@@ -0,0 +1,176 @@
+//! Derive macro for the `ChunkCodec` trait of `riffu`, enabled by its `derive` feature.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, GenericParam, Lifetime, LitStr, Type,
+};
+
+/// Implements `ChunkCodec` for a structure whose fields are laid out back to back.
+///
+/// The identifier of the chunk is given by `#[chunk(id = "fmt ")]`. Every field must implement
+/// `riffu::codec::Field`, except the last one which can instead take the rest of the payload as
+/// a `&[u8]` or a `Vec<u8>`.
+#[proc_macro_derive(ChunkCodec, attributes(chunk))]
+pub fn derive_chunk_codec(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let id = chunk_id(input)?;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "ChunkCodec can only be derived for structures with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "ChunkCodec can only be derived for structures",
+            ))
+        }
+    };
+
+    let lifetimes: Vec<_> = input.generics.lifetimes().collect();
+    if lifetimes.len() > 1 || input.generics.params.len() > lifetimes.len() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "ChunkCodec can only be derived for structures with at most one lifetime",
+        ));
+    }
+    let lifetime = match lifetimes.first() {
+        Some(param) => param.lifetime.clone(),
+        None => Lifetime::new("'a", Span::call_site()),
+    };
+
+    let mut fixed = Vec::new();
+    let mut tail = None;
+    for (i, field) in fields.iter().enumerate() {
+        if i + 1 == fields.len() && is_tail(&field.ty) {
+            tail = Some(field);
+        } else {
+            fixed.push(field);
+        }
+    }
+
+    // Each field is read straight into the structure at a constant offset, so no local of the
+    // generated code can be shadowed by a field of the same name.
+    let fixed_names: Vec<_> = fixed.iter().map(|field| &field.ident).collect();
+    let mut offset = quote! { 0 };
+    let mut reads = Vec::new();
+    for field in &fixed {
+        let name = &field.ident;
+        let ty = &field.ty;
+        reads.push(quote! {
+            #name: <#ty as ::riffu::codec::Field>::read(&data[#offset..]),
+        });
+        offset = quote! { #offset + <#ty as ::riffu::codec::Field>::LEN };
+    }
+    let tail_write = match tail {
+        Some(field) => {
+            let name = &field.ident;
+            let ty = &field.ty;
+            reads.push(quote! {
+                #name: <#ty as ::riffu::codec::Tail<#lifetime>>::read(&data[#offset..]),
+            });
+            quote! {
+                writer.write_all(::riffu::codec::Tail::as_bytes(&self.#name))?;
+            }
+        }
+        None => quote! {},
+    };
+    let fixed_len = offset;
+
+    let name = &input.ident;
+    let impl_generics = if input.generics.params.is_empty() {
+        quote! { <#lifetime> }
+    } else {
+        let params = input.generics.params.iter().map(|param| match param {
+            GenericParam::Lifetime(param) => quote! { #param },
+            _ => unreachable!(),
+        });
+        quote! { <#(#params),*> }
+    };
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::riffu::codec::ChunkCodec<#lifetime> for #name #ty_generics {
+            const ID: ::riffu::FourCC = ::riffu::FourCC::from_array(*#id);
+
+            fn decode(data: &#lifetime [u8]) -> ::riffu::error::RiffResult<Self> {
+                ::riffu::codec::check_len(data, #fixed_len)?;
+                Ok(Self {
+                    #(#reads)*
+                })
+            }
+
+            fn encode<W>(&self, writer: &mut W) -> ::riffu::error::RiffResult<()>
+            where
+                W: ::std::io::Write,
+            {
+                #(
+                    ::riffu::codec::Field::write(&self.#fixed_names, writer)?;
+                )*
+                #tail_write
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Reads the identifier from the `#[chunk(id = "...")]` attribute, as a byte string literal.
+fn chunk_id(input: &DeriveInput) -> syn::Result<syn::LitByteStr> {
+    let mut id = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("chunk"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                let value: LitStr = meta.value()?.parse()?;
+                let bytes = value.value().into_bytes();
+                if bytes.len() != 4 || !bytes.iter().all(|b| (0x20..0x7f).contains(b)) {
+                    return Err(Error::new_spanned(
+                        &value,
+                        "chunk id must be 4 printable ASCII characters",
+                    ));
+                }
+                id = Some(syn::LitByteStr::new(&bytes, value.span()));
+                Ok(())
+            } else {
+                Err(meta.error("unsupported chunk attribute"))
+            }
+        })?;
+    }
+    id.ok_or_else(|| {
+        Error::new_spanned(
+            &input.ident,
+            "missing #[chunk(id = \"...\")] attribute for ChunkCodec",
+        )
+    })
+}
+
+/// Whether `ty` takes the rest of the payload, that is `&[u8]` or `Vec<u8>`.
+fn is_tail(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => matches!(&*reference.elem, Type::Slice(_)),
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Vec"),
+        _ => false,
+    }
+}
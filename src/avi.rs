@@ -0,0 +1,73 @@
+use crate::{
+    codec::{check_len, ChunkCodec, Field},
+    error::RiffResult,
+    FourCC,
+};
+use std::io::Write;
+
+pub const AVI_ID: &[u8; 4] = b"AVI ";
+pub const AVIH_ID: &[u8; 4] = b"avih";
+
+/// The content of the `avih` chunk, describing the whole movie.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MainHeader {
+    pub micro_sec_per_frame: u32,
+    pub max_bytes_per_sec: u32,
+    pub padding_granularity: u32,
+    pub flags: u32,
+    pub total_frames: u32,
+    pub initial_frames: u32,
+    pub streams: u32,
+    pub suggested_buffer_size: u32,
+    pub width: u32,
+    pub height: u32,
+    pub reserved: [u32; 4],
+}
+
+impl MainHeader {
+    /// The number of bytes the header takes.
+    pub const LEN: usize = 10 * u32::LEN + <[u32; 4]>::LEN;
+}
+
+impl<'a> ChunkCodec<'a> for MainHeader {
+    const ID: FourCC = FourCC::from_array(*AVIH_ID);
+
+    fn decode(data: &'a [u8]) -> RiffResult<Self> {
+        check_len(data, MainHeader::LEN)?;
+        let word = |index: usize| u32::read(&data[index * u32::LEN..]);
+        Ok(MainHeader {
+            micro_sec_per_frame: word(0),
+            max_bytes_per_sec: word(1),
+            padding_granularity: word(2),
+            flags: word(3),
+            total_frames: word(4),
+            initial_frames: word(5),
+            streams: word(6),
+            suggested_buffer_size: word(7),
+            width: word(8),
+            height: word(9),
+            reserved: <[u32; 4]>::read(&data[10 * u32::LEN..]),
+        })
+    }
+
+    fn encode<W>(&self, writer: &mut W) -> RiffResult<()>
+    where
+        W: Write,
+    {
+        let words = [
+            self.micro_sec_per_frame,
+            self.max_bytes_per_sec,
+            self.padding_granularity,
+            self.flags,
+            self.total_frames,
+            self.initial_frames,
+            self.streams,
+            self.suggested_buffer_size,
+            self.width,
+            self.height,
+        ];
+        words.write(writer)?;
+        self.reserved.write(writer)
+    }
+}
@@ -0,0 +1,170 @@
+//! Commands editing a file, which write the edited file next to its destination and then
+//! rename it over the destination, so that it is never left half-written.
+
+use crate::{open, Args, Error};
+use riffu::{
+    constants::{LIST_ID, RIFF_ID, SEQT_ID_LOWERCASE, SEQT_ID_UPPERCASE},
+    edit::Editor,
+    error::RiffResult,
+    writer::ChunkContents,
+    Chunk, ChunkKind, FourCC, Riff, RiffError,
+};
+use std::borrow::Cow;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+const CONTAINER_IDS: &[&[u8]] = &[RIFF_ID, LIST_ID, SEQT_ID_LOWERCASE, SEQT_ID_UPPERCASE];
+
+pub fn extract(args: &Args) -> Result<(), Error> {
+    let file = open(args)?;
+    let query = args.positional(1, "query")?;
+    let chunk = select_one(&file.as_chunk()?, query)?;
+    let content = chunk.content()?;
+    match args.value("--output") {
+        Some(output) => replace_file(Path::new(output), |writer| {
+            writer.write_all(content)?;
+            Ok(())
+        }),
+        None => {
+            std::io::stdout()
+                .write_all(content)
+                .map_err(RiffError::from)?;
+            Ok(())
+        }
+    }
+}
+
+pub fn insert(args: &Args) -> Result<(), Error> {
+    let into = args.value("--into").ok_or("missing --into")?;
+    let id = parse_id(args.value("--id").ok_or("missing --id")?)?;
+    if CONTAINER_IDS.contains(&&id.as_bytes()[..]) {
+        return Err(format!("{} chunks cannot be inserted as raw chunks", id).into());
+    }
+    let payload = match (args.value("--data"), args.value("--input")) {
+        (Some(data), None) => data.as_bytes().to_vec(),
+        (None, Some(input)) => fs::read(input).map_err(RiffError::from)?,
+        _ => {
+            return Err(Error::Usage(
+                "expected either --data or --input".to_string(),
+            ))
+        }
+    };
+    let index = match args.value("--at") {
+        Some(index) => Some(
+            index
+                .parse::<usize>()
+                .map_err(|_| format!("invalid index {}", index))?,
+        ),
+        None => None,
+    };
+    let contents = ChunkContents::RawData(id, Cow::Owned(payload));
+    save(args, open(args)?, |editor| {
+        match index {
+            Some(index) => editor.insert(into, index, contents)?,
+            None => editor.append(into, contents)?,
+        };
+        Ok(())
+    })
+}
+
+pub fn strip(args: &Args) -> Result<(), Error> {
+    let ids = args
+        .values("--id")
+        .into_iter()
+        .map(parse_id)
+        .collect::<Result<Vec<_>, _>>()?;
+    if ids.is_empty() {
+        return Err("missing --id".into());
+    }
+    save(args, open(args)?, |editor| {
+        for id in &ids {
+            editor.remove(&format!("**/{}", id))?;
+        }
+        Ok(())
+    })
+}
+
+pub fn replace(args: &Args) -> Result<(), Error> {
+    let file = open(args)?;
+    let query = args.positional(1, "query")?;
+    let payload = fs::read(args.positional(2, "input")?).map_err(RiffError::from)?;
+    let chunks = file.as_chunk()?.select(query)?;
+    let id = match chunks.first() {
+        Some(chunk) => chunk.id()?,
+        None => return Err(Error::Usage(format!("no chunk matches {}", query))),
+    };
+    for chunk in &chunks {
+        if chunk.kind() != ChunkKind::Raw || chunk.id()? != id {
+            return Err(Error::Usage(format!(
+                "{} must only match raw chunks with the same identifier",
+                query
+            )));
+        }
+    }
+    let contents = ChunkContents::RawData(id, Cow::Owned(payload));
+    save(args, file, |editor| {
+        editor.replace(query, contents)?;
+        Ok(())
+    })
+}
+
+/// Parses an identifier of 1 to 4 characters, padded with spaces.
+fn parse_id(id: &str) -> Result<FourCC, Error> {
+    id.parse()
+        .map_err(|_| Error::Usage(format!("invalid identifier {:?}", id)))
+}
+
+/// The only chunk matching `query`.
+fn select_one<'a>(root: &Chunk<'a>, query: &str) -> Result<Chunk<'a>, Error> {
+    let mut chunks = root.select(query)?;
+    if chunks.len() != 1 {
+        return Err(Error::Usage(format!(
+            "{} matches {} chunks instead of one",
+            query,
+            chunks.len()
+        )));
+    }
+    Ok(chunks.remove(0))
+}
+
+/// Applies `edit` to `file`, then writes the result to the output, or over the file itself.
+fn save<F>(args: &Args, file: Riff, edit: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut Editor<'_>) -> RiffResult<()>,
+{
+    let input = args.positional(0, "file")?;
+    let output = Path::new(args.value("--output").unwrap_or(input));
+    let mut editor = file.edit()?;
+    edit(&mut editor)?;
+    replace_file(output, |writer| {
+        editor.write(writer)?;
+        Ok(())
+    })
+}
+
+/// Writes a temporary file with `write`, then renames it to `path`, keeping the permissions of
+/// the file it replaces.
+fn replace_file<F>(path: &Path, write: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut BufWriter<&mut fs::File>) -> RiffResult<()>,
+{
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut temp = NamedTempFile::new_in(dir).map_err(RiffError::from)?;
+    {
+        let mut writer = BufWriter::new(temp.as_file_mut());
+        write(&mut writer)?;
+        writer.flush().map_err(RiffError::from)?;
+    }
+    temp.as_file().sync_all().map_err(RiffError::from)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp.path(), metadata.permissions()).map_err(RiffError::from)?;
+    }
+    temp.persist(path)
+        .map_err(|err| RiffError::from(err.error))?;
+    Ok(())
+}
@@ -0,0 +1,257 @@
+//! Commands printing the contents of a file.
+
+use crate::summary::{summarize, Summary};
+use crate::{open, print_json, Args, Error};
+use riffu::{diff, error::RiffResult, registry::Registry, Chunk, ChunkKind, Riff, RiffError};
+use serde::{Serialize, Serializer};
+
+/// A chunk as printed by `tree`.
+#[derive(Serialize)]
+struct Entry {
+    path: String,
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    form_type: Option<String>,
+    offset: usize,
+    size: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<Fields>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Entry>,
+}
+
+impl Entry {
+    fn from_chunk(chunk: &Chunk<'_>) -> RiffResult<Entry> {
+        let form_type = match chunk.kind() {
+            ChunkKind::List => Some(chunk.chunk_type()?.to_string()),
+            _ => None,
+        };
+        let children = match chunk.kind() {
+            ChunkKind::Raw => Vec::new(),
+            _ => chunk
+                .iter()?
+                .map(|child| Entry::from_chunk(&child?))
+                .collect::<RiffResult<_>>()?,
+        };
+        Ok(Entry {
+            path: chunk.path(),
+            id: chunk.id()?.to_string(),
+            form_type,
+            offset: chunk.offset(),
+            size: chunk.payload_len()?,
+            summary: known_summary(chunk).map(Fields),
+            children,
+        })
+    }
+
+    fn print(&self, depth: usize) {
+        let name = match &self.form_type {
+            Some(form_type) => format!("{}:{}", self.id, form_type),
+            None => self.id.clone(),
+        };
+        let indent = "  ".repeat(depth);
+        println!(
+            "{}{:<w$} offset {:>10}  size {:>10}",
+            indent,
+            name,
+            self.offset,
+            self.size,
+            w = 24usize.saturating_sub(indent.len())
+        );
+        if let Some(Fields(summary)) = &self.summary {
+            for (key, value) in summary {
+                println!("{}    {} = {}", indent, key, value);
+            }
+        }
+        for child in &self.children {
+            child.print(depth + 1);
+        }
+    }
+}
+
+/// A summary, serialized as an object keeping the order of the fields.
+struct Fields(Summary);
+
+impl Serialize for Fields {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.0.iter().map(|(key, value)| (key, value)))
+    }
+}
+
+/// The summary of `chunk`, leaving out chunks too damaged to decode.
+fn known_summary(chunk: &Chunk<'_>) -> Option<Summary> {
+    summarize(chunk).ok().flatten()
+}
+
+#[derive(Serialize)]
+struct Info {
+    path: String,
+    size: u64,
+    form_type: Option<String>,
+    description: Option<String>,
+    chunks: usize,
+    issues: Vec<String>,
+    known: Vec<KnownChunk>,
+}
+
+#[derive(Serialize)]
+struct KnownChunk {
+    path: String,
+    offset: usize,
+    summary: Fields,
+}
+
+#[derive(Serialize)]
+struct Dump {
+    path: String,
+    offset: usize,
+    size: u32,
+    payload: String,
+}
+
+pub fn tree(args: &Args) -> Result<(), Error> {
+    let file = open(args)?;
+    let entry = Entry::from_chunk(&file.as_chunk()?)?;
+    if args.json {
+        print_json(&entry);
+    } else {
+        entry.print(0);
+    }
+    Ok(())
+}
+
+pub fn info(args: &Args) -> Result<(), Error> {
+    let file = open(args)?;
+    let root = file.as_chunk()?;
+    let registry = Registry::new();
+    let form_type = match root.kind() {
+        ChunkKind::List => Some(root.chunk_type()?),
+        _ => None,
+    };
+    let mut chunks = 0;
+    let mut known = Vec::new();
+    for entry in root.walk() {
+        let chunk = entry?.chunk;
+        chunks += 1;
+        if let Some(summary) = known_summary(&chunk) {
+            known.push(KnownChunk {
+                path: chunk.path(),
+                offset: chunk.offset(),
+                summary: Fields(summary),
+            });
+        }
+    }
+    let path = args.positional(0, "file")?;
+    let info = Info {
+        path: path.to_string(),
+        size: std::fs::metadata(path).map_err(RiffError::from)?.len(),
+        form_type: form_type.map(|form_type| form_type.to_string()),
+        description: form_type
+            .and_then(|form_type| registry.form_type(form_type))
+            .map(|description| description.to_string()),
+        chunks,
+        issues: file
+            .validate()
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect(),
+        known,
+    };
+    if args.json {
+        print_json(&info);
+        return Ok(());
+    }
+    println!("file:      {}", info.path);
+    println!("size:      {} bytes", info.size);
+    if let Some(form_type) = &info.form_type {
+        let description = info.description.as_deref().unwrap_or("unknown");
+        println!("form type: {} ({})", form_type, description);
+    }
+    println!("chunks:    {}", info.chunks);
+    if info.issues.is_empty() {
+        println!("issues:    none");
+    } else {
+        println!("issues:");
+        for issue in &info.issues {
+            println!("    {}", issue);
+        }
+    }
+    for chunk in &info.known {
+        println!("{} at offset {}", chunk.path, chunk.offset);
+        for (key, value) in &chunk.summary.0 {
+            println!("    {} = {}", key, value);
+        }
+    }
+    Ok(())
+}
+
+pub fn dump(args: &Args) -> Result<(), Error> {
+    let file = open(args)?;
+    let query = args.positional(1, "query")?;
+    let chunks = file.as_chunk()?.select(query)?;
+    if chunks.is_empty() {
+        return Err(Error::Usage(format!("no chunk matches {}", query)));
+    }
+    let mut dumps = Vec::new();
+    for chunk in &chunks {
+        dumps.push(Dump {
+            path: chunk.path(),
+            offset: chunk.offset(),
+            size: chunk.payload_len()?,
+            payload: chunk
+                .content()?
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+        });
+        if !args.json {
+            println!(
+                "{} at offset {}, {} bytes",
+                chunk.path(),
+                chunk.offset(),
+                chunk.payload_len()?
+            );
+            hex_dump(chunk.content()?);
+        }
+    }
+    if args.json {
+        print_json(&dumps);
+    }
+    Ok(())
+}
+
+pub fn diff(args: &Args) -> Result<(), Error> {
+    let old = open(args)?;
+    let new = Riff::from_path_with_options(args.positional(1, "new file")?, args.options.clone())?;
+    let differences = diff::diff(&old.as_chunk()?, &new.as_chunk()?)?;
+    if args.json {
+        print_json(&differences);
+    } else {
+        for difference in &differences {
+            println!("{}", difference);
+        }
+    }
+    Ok(())
+}
+
+/// Prints `data` as rows of 16 bytes, with their offsets and printable characters.
+fn hex_dump(data: &[u8]) {
+    for (row, bytes) in data.chunks(16).enumerate() {
+        let hex: Vec<_> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let text: String = bytes
+            .iter()
+            .map(|&b| {
+                if (0x20..0x7f).contains(&b) {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        println!("{:08x}  {:<47}  |{}|", row * 16, hex.join(" "), text);
+    }
+}
@@ -0,0 +1,172 @@
+//! Command-line tool to inspect and edit RIFF files.
+
+extern crate riffu;
+
+mod edit;
+mod inspect;
+mod summary;
+
+use riffu::{ParseOptions, Riff, RiffError};
+use serde::Serialize;
+use std::process::exit;
+
+const USAGE: &str = "\
+usage: riffu <command> [options] <file> [arguments]
+
+commands:
+    tree <file>            print the chunks of the file, with their offsets and sizes
+    info <file>            print the form type, validation issues and known chunks
+    dump <file> <query>    hex-dump the payload of the chunks matching the query
+    diff <old> <new>       print the chunks added, removed, moved or changed
+    extract <file> <query> [-o <output>]
+                           write the payload of the chunk matching the query
+    insert <file> --into <query> --id <id> (--data <text> | --input <file>) [--at <index>]
+                           add a chunk to the containers matching the query
+    strip <file> --id <id>...
+                           remove every chunk with one of the identifiers
+    replace <file> <query> <input>
+                           replace the payload of the chunks matching the query
+
+options:
+    --json                 print JSON instead of text
+    --lenient              recover from common mistakes instead of failing
+    -o, --output <file>    where edits are written, instead of the file itself";
+
+/// Options followed by a value.
+const VALUED: &[&str] = &["--into", "--id", "--data", "--input", "--at", "--output"];
+
+/// The parsed command line.
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    /// Options followed by a value, in the order they were given.
+    values: Vec<(String, String)>,
+    json: bool,
+    options: ParseOptions,
+}
+
+impl Args {
+    fn parse() -> Result<Args, String> {
+        let mut args = std::env::args().skip(1);
+        let command = args.next().ok_or("missing command")?;
+        let mut result = Args {
+            command,
+            positional: Vec::new(),
+            values: Vec::new(),
+            json: false,
+            options: ParseOptions::strict(),
+        };
+        while let Some(arg) = args.next() {
+            let arg = match arg.as_str() {
+                "-o" => "--output".to_string(),
+                _ => arg,
+            };
+            match arg.as_str() {
+                "--json" => result.json = true,
+                "--lenient" => result.options = ParseOptions::lenient(),
+                _ if VALUED.contains(&arg.as_str()) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    result.values.push((arg, value));
+                }
+                _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+                _ => result.positional.push(arg),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Every value given to the option `name`.
+    fn values(&self, name: &str) -> Vec<&str> {
+        self.values
+            .iter()
+            .filter(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    /// The last value given to the option `name`.
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values(name).pop()
+    }
+
+    /// The positional argument at `index`, named `name` in error messages.
+    fn positional(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(|arg| arg.as_str())
+            .ok_or_else(|| format!("missing {}", name))
+    }
+}
+
+fn main() {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("riffu: {}\n\n{}", message, USAGE);
+            exit(2);
+        }
+    };
+    let result = match args.command.as_str() {
+        "tree" => inspect::tree(&args),
+        "info" => inspect::info(&args),
+        "dump" => inspect::dump(&args),
+        "diff" => inspect::diff(&args),
+        "extract" => edit::extract(&args),
+        "insert" => edit::insert(&args),
+        "strip" => edit::strip(&args),
+        "replace" => edit::replace(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(Error::Usage(format!("unknown command {}", other))),
+    };
+    match result {
+        Ok(()) => {}
+        Err(Error::Usage(message)) => {
+            eprintln!("riffu: {}\nrun `riffu help` for usage", message);
+            exit(2);
+        }
+        Err(Error::Riff(err)) => {
+            eprintln!("riffu: {}", err);
+            exit(1);
+        }
+    }
+}
+
+enum Error {
+    Usage(String),
+    Riff(RiffError),
+}
+
+impl From<RiffError> for Error {
+    fn from(err: RiffError) -> Self {
+        Error::Riff(err)
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Usage(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Usage(message.to_string())
+    }
+}
+
+fn open(args: &Args) -> Result<Riff, Error> {
+    let path = args.positional(0, "file")?;
+    Ok(Riff::from_path_with_options(path, args.options.clone())?)
+}
+
+fn print_json<T>(value: &T)
+where
+    T: Serialize,
+{
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
@@ -0,0 +1,123 @@
+//! Typed summaries of the chunks whose layout is known.
+
+use riffu::{avi::MainHeader, dls::WaveFormat, error::RiffResult, webp::Vp8x, Chunk, ChunkCodec};
+
+/// The decoded fields of a chunk, in the order they should be shown.
+pub type Summary = Vec<(&'static str, String)>;
+
+#[derive(ChunkCodec)]
+#[chunk(id = "anih")]
+struct AnimationHeader {
+    _size: u32,
+    frames: u32,
+    steps: u32,
+    width: u32,
+    height: u32,
+    bit_count: u32,
+    _planes: u32,
+    display_rate: u32,
+    flags: u32,
+}
+
+#[derive(ChunkCodec)]
+#[chunk(id = "bext")]
+struct BroadcastExtension<'a> {
+    description: [u8; 256],
+    originator: [u8; 32],
+    originator_reference: [u8; 32],
+    origination_date: [u8; 10],
+    origination_time: [u8; 8],
+    time_reference_low: u32,
+    time_reference_high: u32,
+    version: u16,
+    _umid: [u8; 64],
+    _loudness: [u16; 5],
+    _reserved: [u8; 180],
+    coding_history: &'a [u8],
+}
+
+/// Decodes `chunk` if its layout is known.
+pub fn summarize(chunk: &Chunk<'_>) -> RiffResult<Option<Summary>> {
+    let summary = match chunk.id()?.as_bytes() {
+        b"fmt " => {
+            let format = chunk.decode::<WaveFormat>()?;
+            vec![
+                ("format_tag", format!("{:#06x}", format.format_tag)),
+                ("channels", format.channels.to_string()),
+                ("samples_per_sec", format.samples_per_sec.to_string()),
+                ("avg_bytes_per_sec", format.avg_bytes_per_sec.to_string()),
+                ("block_align", format.block_align.to_string()),
+                ("bits_per_sample", format.bits_per_sample.to_string()),
+            ]
+        }
+        b"avih" => {
+            let header = chunk.decode::<MainHeader>()?;
+            vec![
+                ("width", header.width.to_string()),
+                ("height", header.height.to_string()),
+                ("streams", header.streams.to_string()),
+                ("total_frames", header.total_frames.to_string()),
+                (
+                    "micro_sec_per_frame",
+                    header.micro_sec_per_frame.to_string(),
+                ),
+                ("max_bytes_per_sec", header.max_bytes_per_sec.to_string()),
+                ("flags", format!("{:#x}", header.flags)),
+            ]
+        }
+        b"anih" => {
+            let header = chunk.decode::<AnimationHeader>()?;
+            vec![
+                ("frames", header.frames.to_string()),
+                ("steps", header.steps.to_string()),
+                ("width", header.width.to_string()),
+                ("height", header.height.to_string()),
+                ("bit_count", header.bit_count.to_string()),
+                ("display_rate", header.display_rate.to_string()),
+                ("flags", format!("{:#x}", header.flags)),
+            ]
+        }
+        b"VP8X" => {
+            let header = Vp8x::from_bytes(chunk.content()?).map_err(|err| {
+                err.or_location(|| riffu::error::Location::new(chunk.offset(), chunk.path()))
+            })?;
+            vec![
+                ("canvas_width", header.canvas_width.to_string()),
+                ("canvas_height", header.canvas_height.to_string()),
+                ("icc_profile", header.has_icc_profile().to_string()),
+                ("alpha", header.has_alpha().to_string()),
+                ("exif", header.has_exif().to_string()),
+                ("xmp", header.has_xmp().to_string()),
+                ("animation", header.has_animation().to_string()),
+            ]
+        }
+        b"bext" => {
+            let extension = chunk.decode::<BroadcastExtension>()?;
+            let time_reference = (u64::from(extension.time_reference_high) << 32)
+                | u64::from(extension.time_reference_low);
+            vec![
+                ("description", text(&extension.description)),
+                ("originator", text(&extension.originator)),
+                (
+                    "originator_reference",
+                    text(&extension.originator_reference),
+                ),
+                ("origination_date", text(&extension.origination_date)),
+                ("origination_time", text(&extension.origination_time)),
+                ("time_reference", time_reference.to_string()),
+                ("version", extension.version.to_string()),
+                ("coding_history", text(extension.coding_history)),
+            ]
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(summary))
+}
+
+/// Reads a string padded with NUL bytes.
+fn text(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end])
+        .trim_end()
+        .to_string()
+}
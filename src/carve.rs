@@ -0,0 +1,305 @@
+use crate::{
+    constants::RIFF_ID, fourcc::is_printable, options::ParseOptions, Chunk, ChunkKind, FourCC,
+};
+use std::convert::TryFrom;
+use std::io::{self, Read};
+
+const RIFX_ID: &[u8] = b"RIFX";
+const RF64_ID: &[u8] = b"RF64";
+const DS64_ID: &[u8] = b"ds64";
+
+/// The size field of an `RF64` header, telling that the real size lives in `ds64`.
+const RF64_SIZE_IN_DS64: u32 = 0xffff_ffff;
+
+/// The number of bytes needed to read the size of any container.
+const PROBE_LEN: usize = 28;
+
+/// The number of bytes a `ScanReader` lets pass before dropping them from its buffer.
+const COMPACT_THRESHOLD: usize = 64 * 1024;
+
+/// The number of bytes of a container a `ScanReader` buffers by default.
+pub const DEFAULT_MAX_LEN: u64 = 256 * 1024 * 1024;
+
+/// The kind of header a container starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    /// `RIFF`, with little-endian sizes.
+    Riff,
+    /// `RIFX`, with big-endian sizes.
+    Rifx,
+    /// `RF64`, whose size is stored as 64 bits in a leading `ds64` chunk.
+    Rf64,
+}
+
+/// A container found by a scan.
+#[derive(Debug, Clone)]
+pub struct Found {
+    /// Absolute offset of the header in the scanned data.
+    pub offset: u64,
+    pub container: Container,
+    pub form_type: FourCC,
+    /// The number of bytes of the container present in the data, including its header.
+    pub len: u64,
+    /// Whether the data ends before the size of the container says it should.
+    pub truncated: bool,
+}
+
+/// Reads the container header at the start of `data`, returning its kind and declared size.
+fn probe(data: &[u8]) -> Option<(Container, u64)> {
+    let header = data.get(0..12)?;
+    let size = [header[4], header[5], header[6], header[7]];
+    match &header[0..4] {
+        RIFF_ID => Some((Container::Riff, u64::from(u32::from_le_bytes(size)))),
+        RIFX_ID => Some((Container::Rifx, u64::from(u32::from_be_bytes(size)))),
+        RF64_ID if u32::from_le_bytes(size) == RF64_SIZE_IN_DS64 => {
+            if data.get(12..16)? != DS64_ID {
+                return None;
+            }
+            let mut riff_size = [0; 8];
+            riff_size.copy_from_slice(data.get(20..28)?);
+            Some((Container::Rf64, u64::from_le_bytes(riff_size)))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `data` could start a container, judging from its first bytes only.
+fn is_candidate(data: &[u8]) -> bool {
+    matches!(
+        data.get(0..4),
+        Some(RIFF_ID) | Some(RIFX_ID) | Some(RF64_ID)
+    )
+}
+
+/// Validates the container at the start of `data`, which holds everything from its header to
+/// the end of the available bytes.
+fn validate(data: &[u8], offset: u64) -> Option<Found> {
+    let (container, size) = probe(data)?;
+    let form_type = FourCC::new(&data[8..12]).ok()?;
+    if !is_printable(form_type.as_bytes()) || size < 4 {
+        return None;
+    }
+    let declared = size.checked_add(8)?;
+    let truncated = (data.len() as u64) < declared;
+    let len = declared.min(data.len() as u64);
+    let data = &data[..len as usize];
+
+    // Walk the top level chunks, allowing the last one to be cut if the data ends early.
+    let mut cursor = 12;
+    let mut children = 0;
+    while cursor < data.len() {
+        let header = match data.get(cursor..cursor + 8) {
+            Some(header) => header,
+            None if truncated => break,
+            None => return None,
+        };
+        if !is_printable(&header[0..4]) {
+            return None;
+        }
+        let size = [header[4], header[5], header[6], header[7]];
+        let size = match container {
+            Container::Rifx => u32::from_be_bytes(size),
+            _ => u32::from_le_bytes(size),
+        };
+        // The `data` chunk of an `RF64` has its size in `ds64` too.
+        let size = match (container, size) {
+            (Container::Rf64, RF64_SIZE_IN_DS64) => data.len() - cursor - 8,
+            _ => size as usize,
+        };
+        let next = (cursor + 8)
+            .checked_add(size)
+            .and_then(|next| next.checked_add(size % 2))?;
+        if next > data.len() + 1 && !truncated {
+            return None;
+        }
+        children += 1;
+        cursor = next;
+    }
+    if children == 0 {
+        return None;
+    }
+    if container == Container::Riff && !parses(data) {
+        return None;
+    }
+    Some(Found {
+        offset,
+        container,
+        form_type,
+        len,
+        truncated,
+    })
+}
+
+/// Whether every chunk nested in `data` parses, clamping those cut by the end of the data.
+fn parses(data: &[u8]) -> bool {
+    fn walk(chunk: &Chunk<'_>) -> bool {
+        if chunk.kind() == ChunkKind::Raw {
+            return true;
+        }
+        match chunk.iter() {
+            Ok(mut children) => children.all(|child| match child {
+                Ok(child) => walk(&child),
+                Err(_) => false,
+            }),
+            Err(_) => false,
+        }
+    }
+    match Chunk::from_bytes_with_options(data, ParseOptions::lenient()) {
+        Ok(chunk) => walk(&chunk),
+        Err(_) => false,
+    }
+}
+
+/// Finds the containers embedded in `data`, such as a disk image or a memory dump.
+///
+/// Every position holding a `RIFF`, `RIFX` or `RF64` header is tried, so containers nested in
+/// or overlapping with others are found too. A candidate is kept if its top level chunks fit
+/// its size, except for a last one cut by the end of the data, and if it parses.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::carve::scan;
+/// let mut image = vec![0; 100];
+/// image.extend_from_slice(b"RIFF\x0e\0\0\0WAVEdata\x02\0\0\0ab");
+/// image.extend_from_slice(b"RIFF\0\0\0\0 and more bytes");
+/// let found: Vec<_> = scan(&image).collect();
+/// assert_eq!(found.len(), 1);
+/// assert_eq!(found[0].offset, 100);
+/// assert_eq!(found[0].len, 22);
+/// ```
+pub fn scan(data: &[u8]) -> Scan<'_> {
+    Scan { data, pos: 0 }
+}
+
+/// An iterator over the containers in a slice, returned by `scan`.
+#[derive(Debug)]
+pub struct Scan<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Scan<'a> {
+    type Item = Found;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.data.len() {
+            let pos = self.pos;
+            self.pos += 1;
+            let data = &self.data[pos..];
+            if is_candidate(data) {
+                if let Some(found) = validate(data, pos as u64) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Finds the containers embedded in the bytes read from `reader`, like `scan` does.
+///
+/// Only the bytes that may still be part of a container are kept in memory, but validating a
+/// candidate requires buffering all of it. At most `DEFAULT_MAX_LEN` bytes of a candidate are
+/// buffered, or the limit set by `ScanReader::with_max_len`, and larger containers are reported
+/// as truncated at that length.
+pub fn scan_reader<R>(reader: R) -> ScanReader<R>
+where
+    R: Read,
+{
+    ScanReader {
+        reader,
+        buffer: Vec::new(),
+        buffer_offset: 0,
+        pos: 0,
+        eof: false,
+        max_len: DEFAULT_MAX_LEN,
+    }
+}
+
+/// An iterator over the containers in a `Read` source, returned by `scan_reader`.
+#[derive(Debug)]
+pub struct ScanReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// Absolute offset of the first byte of `buffer`.
+    buffer_offset: u64,
+    pos: usize,
+    eof: bool,
+    /// The number of bytes of a container buffered at most, including its header.
+    max_len: u64,
+}
+
+impl<R> ScanReader<R>
+where
+    R: Read,
+{
+    /// Buffers at most `max_len` bytes of each container.
+    pub fn with_max_len(mut self, max_len: u64) -> ScanReader<R> {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Reads until `buffer` holds `len` bytes or the reader is exhausted.
+    fn fill(&mut self, len: usize) -> io::Result<()> {
+        let mut chunk = [0; 8192];
+        while self.buffer.len() < len && !self.eof {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the bytes that were scanned already.
+    fn compact(&mut self) {
+        if self.pos >= COMPACT_THRESHOLD {
+            self.buffer.drain(..self.pos);
+            self.buffer_offset += self.pos as u64;
+            self.pos = 0;
+        }
+    }
+}
+
+impl<R> Iterator for ScanReader<R>
+where
+    R: Read,
+{
+    type Item = io::Result<Found>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.compact();
+            if let Err(err) = self.fill(self.pos + PROBE_LEN) {
+                return Some(Err(err));
+            }
+            if self.pos >= self.buffer.len() {
+                return None;
+            }
+            let pos = self.pos;
+            self.pos += 1;
+            if !is_candidate(&self.buffer[pos..]) {
+                continue;
+            }
+            let mut candidate_end = self.buffer.len();
+            if let Some((_, size)) = probe(&self.buffer[pos..]) {
+                let needed = size
+                    .saturating_add(8)
+                    .min(self.max_len.max(PROBE_LEN as u64));
+                let needed = usize::try_from(needed).unwrap_or(usize::MAX);
+                candidate_end = pos.saturating_add(needed);
+                if let Err(err) = self.fill(candidate_end) {
+                    return Some(Err(err));
+                }
+            }
+            let candidate = &self.buffer[pos..candidate_end.min(self.buffer.len())];
+            let offset = self.buffer_offset + pos as u64;
+            if let Some(found) = validate(candidate, offset) {
+                return Some(Ok(found));
+            }
+        }
+    }
+}
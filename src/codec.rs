@@ -0,0 +1,169 @@
+use crate::{error::RiffResult, writer::ChunkContents, FourCC, RiffError};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// A structure stored as the payload of a chunk.
+///
+/// With the `derive` feature, `#[derive(ChunkCodec)]` implements this trait for structures
+/// whose fields are laid out back to back in little-endian order. Fields can be integers,
+/// `FourCC`s and fixed arrays of those, and the last field can also be a `&[u8]` or a `Vec<u8>`
+/// taking the rest of the payload. The identifier is given by a `#[chunk(id = "...")]`
+/// attribute.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{codec::{check_len, ChunkCodec, Field}, error::RiffResult, FourCC, Riff};
+/// use std::io::Write;
+///
+/// struct Fact {
+///     sample_count: u32,
+/// }
+///
+/// impl<'a> ChunkCodec<'a> for Fact {
+///     const ID: FourCC = FourCC::from_array(*b"fact");
+///
+///     fn decode(data: &'a [u8]) -> RiffResult<Self> {
+///         check_len(data, u32::LEN)?;
+///         Ok(Fact { sample_count: u32::read(data) })
+///     }
+///
+///     fn encode<W: Write>(&self, writer: &mut W) -> RiffResult<()> {
+///         self.sample_count.write(writer)
+///     }
+/// }
+///
+/// let file = Riff::from_path("test_assets/Chimes.wav").unwrap();
+/// let fact = file.as_chunk().unwrap().select_first("fact").unwrap().unwrap();
+/// assert_eq!(fact.decode::<Fact>().unwrap().sample_count, 15876);
+/// ```
+pub trait ChunkCodec<'a>: Sized {
+    /// The identifier of the chunk holding this structure.
+    const ID: FourCC;
+
+    /// Reads the structure from the payload of a chunk.
+    fn decode(data: &'a [u8]) -> RiffResult<Self>;
+
+    /// Writes the payload of a chunk holding the structure.
+    fn encode<W>(&self, writer: &mut W) -> RiffResult<()>
+    where
+        W: Write;
+
+    /// A chunk holding the structure, ready to be written.
+    fn to_contents(&self) -> RiffResult<ChunkContents<'static>> {
+        let mut payload = Vec::new();
+        self.encode(&mut payload)?;
+        Ok(ChunkContents::RawData(Self::ID, Cow::Owned(payload)))
+    }
+}
+
+/// A value of fixed length, as found in the fields of a `ChunkCodec` structure.
+pub trait Field: Sized {
+    /// The number of bytes the value takes.
+    const LEN: usize;
+
+    /// Reads the value from the first `LEN` bytes of `data`, which holds at least that many.
+    fn read(data: &[u8]) -> Self;
+
+    fn write<W>(&self, writer: &mut W) -> RiffResult<()>
+    where
+        W: Write;
+}
+
+macro_rules! integer_field {
+    ( $( $ty : ty ),* ) => {
+        $(
+            impl Field for $ty {
+                const LEN: usize = std::mem::size_of::<$ty>();
+
+                fn read(data: &[u8]) -> Self {
+                    let mut bytes = [0; std::mem::size_of::<$ty>()];
+                    bytes.copy_from_slice(&data[..Self::LEN]);
+                    <$ty>::from_le_bytes(bytes)
+                }
+
+                fn write<W>(&self, writer: &mut W) -> RiffResult<()>
+                where
+                    W: Write,
+                {
+                    writer.write_all(&self.to_le_bytes())?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+integer_field!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl Field for FourCC {
+    const LEN: usize = 4;
+
+    fn read(data: &[u8]) -> Self {
+        FourCC::new_unchecked([data[0], data[1], data[2], data[3]])
+    }
+
+    fn write<W>(&self, writer: &mut W) -> RiffResult<()>
+    where
+        W: Write,
+    {
+        writer.write_all(self.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> Field for [T; N]
+where
+    T: Field,
+{
+    const LEN: usize = T::LEN * N;
+
+    fn read(data: &[u8]) -> Self {
+        std::array::from_fn(|i| T::read(&data[i * T::LEN..]))
+    }
+
+    fn write<W>(&self, writer: &mut W) -> RiffResult<()>
+    where
+        W: Write,
+    {
+        for value in self {
+            value.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// The last field of a `ChunkCodec` structure, taking the rest of the payload.
+pub trait Tail<'a>: Sized {
+    fn read(data: &'a [u8]) -> Self;
+
+    fn as_bytes(&self) -> &[u8];
+}
+
+impl<'a> Tail<'a> for &'a [u8] {
+    fn read(data: &'a [u8]) -> Self {
+        data
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<'a> Tail<'a> for Vec<u8> {
+    fn read(data: &'a [u8]) -> Self {
+        data.to_vec()
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Checks that `data` holds at least the `needed` bytes of the fixed fields of a structure.
+pub fn check_len(data: &[u8], needed: usize) -> RiffResult<()> {
+    if data.len() < needed {
+        return Err(RiffError::malformed("payload too short"));
+    }
+    Ok(())
+}
@@ -0,0 +1,332 @@
+use crate::{
+    avi::{MainHeader, AVIH_ID},
+    dls::{WaveFormat, FMT_ID},
+    error::RiffResult,
+    Chunk, ChunkCodec, ChunkKind, FourCC,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// The fields of a chunk whose layout is known, decoded through its `ChunkCodec`, along with
+/// the number of bytes they take.
+fn typed_fields(id: &[u8; 4], payload: &[u8]) -> Option<(usize, Vec<(&'static str, u32)>)> {
+    let fields = match id {
+        FMT_ID => {
+            let format = WaveFormat::decode(payload).ok()?;
+            let fields = vec![
+                ("format_tag", format.format_tag.into()),
+                ("channels", format.channels.into()),
+                ("samples_per_sec", format.samples_per_sec),
+                ("avg_bytes_per_sec", format.avg_bytes_per_sec),
+                ("block_align", format.block_align.into()),
+                ("bits_per_sample", format.bits_per_sample.into()),
+            ];
+            (WaveFormat::LEN, fields)
+        }
+        AVIH_ID => {
+            let header = MainHeader::decode(payload).ok()?;
+            let fields = vec![
+                ("micro_sec_per_frame", header.micro_sec_per_frame),
+                ("max_bytes_per_sec", header.max_bytes_per_sec),
+                ("padding_granularity", header.padding_granularity),
+                ("flags", header.flags),
+                ("total_frames", header.total_frames),
+                ("initial_frames", header.initial_frames),
+                ("streams", header.streams),
+                ("suggested_buffer_size", header.suggested_buffer_size),
+                ("width", header.width),
+                ("height", header.height),
+            ];
+            (MainHeader::LEN, fields)
+        }
+        _ => return None,
+    };
+    Some(fields)
+}
+
+/// A change between two versions of a chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum DifferenceKind {
+    /// The chunk only exists in the new file, at `offset`.
+    Added { offset: usize },
+    /// The chunk only exists in the old file, at `offset`.
+    Removed { offset: usize },
+    /// The chunk moved from index `from` among its siblings to index `to`.
+    Moved { from: usize, to: usize },
+    /// The payload of a raw chunk changed size.
+    SizeChanged { old: u32, new: u32 },
+    /// The payloads of a raw chunk differ, starting `offset` bytes into them. For a chunk whose
+    /// layout is known, only bytes past its typed fields are compared this way.
+    PayloadChanged { offset: usize },
+    /// A field of a chunk whose layout is known changed.
+    FieldChanged {
+        field: &'static str,
+        old: u32,
+        new: u32,
+    },
+}
+
+/// A change, along with the path of the chunk it concerns.
+///
+/// Paths are queries relative to the root, as accepted by `Chunk::select`, so that they
+/// designate the chunk in either file. The root itself is `.`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Difference {
+    pub path: String,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub kind: DifferenceKind,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.kind {
+            DifferenceKind::Added { offset } => write!(f, "added at offset {}", offset),
+            DifferenceKind::Removed { offset } => write!(f, "removed from offset {}", offset),
+            DifferenceKind::Moved { from, to } => write!(f, "moved from index {} to {}", from, to),
+            DifferenceKind::SizeChanged { old, new } => {
+                write!(f, "size changed from {} to {} bytes", old, new)
+            }
+            DifferenceKind::PayloadChanged { offset } => {
+                write!(f, "payload differs from byte {}", offset)
+            }
+            DifferenceKind::FieldChanged { field, old, new } => {
+                write!(f, "{} changed from {} to {}", field, old, new)
+            }
+        }
+    }
+}
+
+/// Identifies a chunk among its siblings: its identifier, and its form type for lists.
+type Key = (FourCC, Option<FourCC>);
+
+fn key(chunk: &Chunk<'_>) -> RiffResult<Key> {
+    let chunk_type = match chunk.kind() {
+        ChunkKind::List => Some(chunk.chunk_type()?),
+        _ => None,
+    };
+    Ok((chunk.id()?, chunk_type))
+}
+
+/// Lists the changes turning `old` into `new`.
+///
+/// Children are aligned by identifier and form type, the n-th of a kind in `old` matching the
+/// n-th of that kind in `new`. Matched chunks that changed position relative to each other are
+/// reported as moved. Only raw chunks report size and payload changes, since those of their
+/// containers follow.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{diff::{diff, DifferenceKind}, Chunk};
+/// let old = Chunk::from_bytes(b"RIFF\x16\0\0\0smplabcd\x02\0\0\0abefgh\x00\0\0\0").unwrap();
+/// let new = Chunk::from_bytes(b"RIFF\x16\0\0\0smplefgh\x00\0\0\0abcd\x02\0\0\0ax").unwrap();
+/// let differences = diff(&old, &new).unwrap();
+/// assert_eq!(differences.len(), 2);
+/// assert_eq!(differences[0].path, "abcd");
+/// assert_eq!(differences[0].kind, DifferenceKind::Moved { from: 0, to: 1 });
+/// assert_eq!(differences[1].kind, DifferenceKind::PayloadChanged { offset: 1 });
+/// ```
+pub fn diff(old: &Chunk<'_>, new: &Chunk<'_>) -> RiffResult<Vec<Difference>> {
+    let mut result = Vec::new();
+    if key(old)? != key(new)? {
+        let push = |kind| Difference {
+            path: ".".to_string(),
+            kind,
+        };
+        result.push(push(DifferenceKind::Removed {
+            offset: old.offset(),
+        }));
+        result.push(push(DifferenceKind::Added {
+            offset: new.offset(),
+        }));
+        return Ok(result);
+    }
+    compare(old, new, ".", &mut result)?;
+    Ok(result)
+}
+
+/// Compares two chunks with the same key.
+fn compare(
+    old: &Chunk<'_>,
+    new: &Chunk<'_>,
+    path: &str,
+    result: &mut Vec<Difference>,
+) -> RiffResult<()> {
+    let mut push = |kind| {
+        result.push(Difference {
+            path: path.to_string(),
+            kind,
+        })
+    };
+    if old.kind() != ChunkKind::Raw {
+        return compare_children(old, new, path, result);
+    }
+    let (old_payload, new_payload) = (old.content()?, new.content()?);
+    if old_payload.len() != new_payload.len() {
+        push(DifferenceKind::SizeChanged {
+            old: old.payload_len()?,
+            new: new.payload_len()?,
+        });
+    }
+    // Bytes within the typed fields are reported through them, and only the bytes past them
+    // as a payload change.
+    let mut typed_len = 0;
+    let id = old.id()?;
+    if let (Some((len, old_fields)), Some((_, new_fields))) = (
+        typed_fields(id.as_bytes(), old_payload),
+        typed_fields(id.as_bytes(), new_payload),
+    ) {
+        for ((field, old), (_, new)) in old_fields.into_iter().zip(new_fields) {
+            if old != new {
+                push(DifferenceKind::FieldChanged { field, old, new });
+            }
+        }
+        typed_len = len;
+    }
+    let (old_rest, new_rest) = (&old_payload[typed_len..], &new_payload[typed_len..]);
+    let first_difference = old_rest
+        .iter()
+        .zip(new_rest)
+        .position(|(a, b)| a != b)
+        .or_else(|| {
+            let common = old_rest.len().min(new_rest.len());
+            Some(common).filter(|_| old_rest.len() != new_rest.len())
+        });
+    if let Some(offset) = first_difference {
+        push(DifferenceKind::PayloadChanged {
+            offset: typed_len + offset,
+        });
+    }
+    Ok(())
+}
+
+fn compare_children(
+    old: &Chunk<'_>,
+    new: &Chunk<'_>,
+    path: &str,
+    result: &mut Vec<Difference>,
+) -> RiffResult<()> {
+    let old_children = children(old)?;
+    let new_children = children(new)?;
+
+    // Where each (key, occurrence) of the new list is.
+    let mut positions = HashMap::new();
+    for (i, (_, key, occurrence)) in new_children.iter().enumerate() {
+        positions.insert((*key, *occurrence), i);
+    }
+    // Keys shared by siblings in either list, whose paths need an occurrence.
+    let repeated: HashSet<Key> = old_children
+        .iter()
+        .chain(&new_children)
+        .filter(|(_, _, occurrence)| *occurrence > 0)
+        .map(|(_, key, _)| *key)
+        .collect();
+    let matches: Vec<Option<usize>> = old_children
+        .iter()
+        .map(|(_, key, occurrence)| positions.get(&(*key, *occurrence)).copied())
+        .collect();
+    let kept: HashSet<usize> =
+        increasing_subsequence(&matches.iter().flatten().copied().collect::<Vec<_>>())
+            .into_iter()
+            .collect();
+    let mut matched = vec![false; new_children.len()];
+
+    let mut rank = 0;
+    for (i, (child, key, occurrence)) in old_children.iter().enumerate() {
+        let child_path = join(path, key, *occurrence, repeated.contains(key));
+        match matches[i] {
+            Some(j) => {
+                matched[j] = true;
+                if !kept.contains(&rank) {
+                    result.push(Difference {
+                        path: child_path.clone(),
+                        kind: DifferenceKind::Moved { from: i, to: j },
+                    });
+                }
+                rank += 1;
+                compare(child, &new_children[j].0, &child_path, result)?;
+            }
+            None => result.push(Difference {
+                path: child_path,
+                kind: DifferenceKind::Removed {
+                    offset: child.offset(),
+                },
+            }),
+        }
+    }
+    for (j, (child, key, occurrence)) in new_children.iter().enumerate() {
+        if !matched[j] {
+            result.push(Difference {
+                path: join(path, key, *occurrence, repeated.contains(key)),
+                kind: DifferenceKind::Added {
+                    offset: child.offset(),
+                },
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The children of `chunk`, with their key and how many siblings before them share it.
+fn children<'a>(chunk: &Chunk<'a>) -> RiffResult<Vec<(Chunk<'a>, Key, usize)>> {
+    let mut counts: HashMap<Key, usize> = HashMap::new();
+    let mut result = Vec::new();
+    for child in chunk.iter()? {
+        let child = child?;
+        let key = key(&child)?;
+        let count = counts.entry(key).or_default();
+        result.push((child, key, *count));
+        *count += 1;
+    }
+    Ok(result)
+}
+
+/// The path of a child, with its occurrence if it shares its key with a sibling.
+fn join(parent: &str, key: &Key, occurrence: usize, repeated: bool) -> String {
+    let mut result = match parent {
+        "." => String::new(),
+        _ => format!("{}/", parent),
+    };
+    result.push_str(key.0.to_string().trim_end());
+    if let Some(chunk_type) = key.1 {
+        result.push(':');
+        result.push_str(chunk_type.to_string().trim_end());
+    }
+    if repeated {
+        result.push_str(&format!("[{}]", occurrence));
+    }
+    result
+}
+
+/// The indexes into `values` of a longest strictly increasing subsequence.
+fn increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // `tails[k]` is the index of the smallest value ending an increasing run of length `k + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        let k = tails.partition_point(|&t| values[t] < *value);
+        if k > 0 {
+            previous[i] = Some(tails[k - 1]);
+        }
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        result.push(i);
+        current = previous[i];
+    }
+    result.reverse();
+    result
+}
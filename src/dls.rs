@@ -0,0 +1,482 @@
+use crate::{
+    codec::{check_len, ChunkCodec, Field},
+    error::RiffResult,
+    sf2::read_zstr,
+    Chunk, ChunkKind, FourCC, RiffError,
+};
+use std::io::Write;
+
+pub const DLS_ID: &[u8; 4] = b"DLS ";
+pub const VERS_ID: &[u8; 4] = b"vers";
+pub const COLH_ID: &[u8; 4] = b"colh";
+pub const LINS_ID: &[u8; 4] = b"lins";
+pub const INS_ID: &[u8; 4] = b"ins ";
+pub const INSH_ID: &[u8; 4] = b"insh";
+pub const LRGN_ID: &[u8; 4] = b"lrgn";
+pub const RGN_ID: &[u8; 4] = b"rgn ";
+pub const RGN2_ID: &[u8; 4] = b"rgn2";
+pub const RGNH_ID: &[u8; 4] = b"rgnh";
+pub const WSMP_ID: &[u8; 4] = b"wsmp";
+pub const WLNK_ID: &[u8; 4] = b"wlnk";
+pub const LART_ID: &[u8; 4] = b"lart";
+pub const LAR2_ID: &[u8; 4] = b"lar2";
+pub const ART1_ID: &[u8; 4] = b"art1";
+pub const ART2_ID: &[u8; 4] = b"art2";
+pub const PTBL_ID: &[u8; 4] = b"ptbl";
+pub const WVPL_ID: &[u8; 4] = b"wvpl";
+pub const WAVE_ID: &[u8; 4] = b"wave";
+pub const FMT_ID: &[u8; 4] = b"fmt ";
+pub const DATA_ID: &[u8; 4] = b"data";
+pub const INFO_ID: &[u8; 4] = b"INFO";
+pub const INAM_ID: &[u8; 4] = b"INAM";
+
+/// Bit of `Instrument::bank` marking a drum instrument.
+pub const F_INSTRUMENT_DRUMS: u32 = 0x8000_0000;
+
+fn read_u16(data: &[u8], offset: usize) -> RiffResult<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .ok_or_else(|| RiffError::malformed("payload too short"))?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> RiffResult<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .ok_or_else(|| RiffError::malformed("payload too short"))?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Reads the `INAM` of the `LIST INFO` child of `chunk`, if any.
+fn read_name(chunk: &Chunk) -> RiffResult<Option<String>> {
+    for child in chunk.iter()? {
+        let child = child?;
+        if child.is_list_of(INFO_ID)? {
+            for info in child.iter()? {
+                let info = info?;
+                if info.id()?.as_bytes() == INAM_ID {
+                    return Ok(Some(read_zstr(info.content()?)));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The `fmt ` chunk of a wave, without any format-specific extension.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WaveFormat {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub samples_per_sec: u32,
+    pub avg_bytes_per_sec: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+}
+
+impl WaveFormat {
+    /// The number of bytes the format takes, without extension.
+    pub const LEN: usize = 16;
+
+    pub fn from_bytes(data: &[u8]) -> RiffResult<WaveFormat> {
+        Ok(WaveFormat {
+            format_tag: read_u16(data, 0)?,
+            channels: read_u16(data, 2)?,
+            samples_per_sec: read_u32(data, 4)?,
+            avg_bytes_per_sec: read_u32(data, 8)?,
+            block_align: read_u16(data, 12)?,
+            bits_per_sample: read_u16(data, 14)?,
+        })
+    }
+}
+
+/// Decodes the format and ignores any extension following it.
+impl<'a> ChunkCodec<'a> for WaveFormat {
+    const ID: FourCC = FourCC::from_array(*FMT_ID);
+
+    fn decode(data: &'a [u8]) -> RiffResult<Self> {
+        check_len(data, WaveFormat::LEN)?;
+        WaveFormat::from_bytes(data)
+    }
+
+    fn encode<W>(&self, writer: &mut W) -> RiffResult<()>
+    where
+        W: Write,
+    {
+        self.format_tag.write(writer)?;
+        self.channels.write(writer)?;
+        self.samples_per_sec.write(writer)?;
+        self.avg_bytes_per_sec.write(writer)?;
+        self.block_align.write(writer)?;
+        self.bits_per_sample.write(writer)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveLoop {
+    pub loop_type: u32,
+    pub start: u32,
+    pub length: u32,
+}
+
+/// The `wsmp` chunk, found in regions and in waves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WaveSample {
+    pub unity_note: u16,
+    pub fine_tune: i16,
+    pub attenuation: i32,
+    pub options: u32,
+    pub loops: Vec<WaveLoop>,
+}
+
+impl WaveSample {
+    pub fn from_bytes(data: &[u8]) -> RiffResult<WaveSample> {
+        let size = read_u32(data, 0)? as usize;
+        let loop_count = read_u32(data, 16)? as usize;
+        let loops = (0..loop_count)
+            .map(|idx| {
+                let offset = size + idx * 16;
+                Ok(WaveLoop {
+                    loop_type: read_u32(data, offset + 4)?,
+                    start: read_u32(data, offset + 8)?,
+                    length: read_u32(data, offset + 12)?,
+                })
+            })
+            .collect::<RiffResult<_>>()?;
+        Ok(WaveSample {
+            unity_note: read_u16(data, 4)?,
+            fine_tune: read_u16(data, 6)? as i16,
+            attenuation: read_u32(data, 8)? as i32,
+            options: read_u32(data, 12)?,
+            loops,
+        })
+    }
+}
+
+/// The `wlnk` chunk of a region.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WaveLink {
+    pub options: u16,
+    pub phase_group: u16,
+    pub channel: u32,
+    /// Index into the pool table of the wave played by the region.
+    pub table_index: u32,
+}
+
+impl WaveLink {
+    pub fn from_bytes(data: &[u8]) -> RiffResult<WaveLink> {
+        Ok(WaveLink {
+            options: read_u16(data, 0)?,
+            phase_group: read_u16(data, 2)?,
+            channel: read_u32(data, 4)?,
+            table_index: read_u32(data, 8)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionBlock {
+    pub source: u16,
+    pub control: u16,
+    pub destination: u16,
+    pub transform: u16,
+    pub scale: i32,
+}
+
+/// The DLS level an articulator was written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArticulatorLevel {
+    /// An `art1` chunk.
+    Level1,
+    /// An `art2` chunk.
+    Level2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Articulator {
+    pub level: ArticulatorLevel,
+    pub connections: Vec<ConnectionBlock>,
+}
+
+impl Articulator {
+    pub fn from_chunk(chunk: &Chunk) -> RiffResult<Articulator> {
+        let id = chunk.id()?;
+        let level = match id.as_bytes() {
+            ART1_ID => ArticulatorLevel::Level1,
+            ART2_ID => ArticulatorLevel::Level2,
+            _ => return Err(chunk.locate(RiffError::malformed("not an articulator chunk"))),
+        };
+        let data = chunk.content()?;
+        let size = read_u32(data, 0).map_err(|e| chunk.locate(e))? as usize;
+        let count = read_u32(data, 4).map_err(|e| chunk.locate(e))? as usize;
+        let connections = (0..count)
+            .map(|idx| {
+                let offset = size + idx * 12;
+                Ok(ConnectionBlock {
+                    source: read_u16(data, offset)?,
+                    control: read_u16(data, offset + 2)?,
+                    destination: read_u16(data, offset + 4)?,
+                    transform: read_u16(data, offset + 6)?,
+                    scale: read_u32(data, offset + 8)? as i32,
+                })
+            })
+            .collect::<RiffResult<_>>()
+            .map_err(|e| chunk.locate(e))?;
+        Ok(Articulator { level, connections })
+    }
+}
+
+/// Collects the articulators of every `lart` and `lar2` list directly under `chunk`.
+fn read_articulators(chunk: &Chunk) -> RiffResult<Vec<Articulator>> {
+    let mut result = Vec::new();
+    for child in chunk.iter()? {
+        let child = child?;
+        if child.kind() == ChunkKind::List {
+            if let LART_ID | LAR2_ID = child.chunk_type()?.as_bytes() {
+                for art in child.iter()? {
+                    let art = art?;
+                    if let ART1_ID | ART2_ID = art.id()?.as_bytes() {
+                        result.push(Articulator::from_chunk(&art)?);
+                    }
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Region {
+    pub key_range: (u16, u16),
+    pub velocity_range: (u16, u16),
+    pub options: u16,
+    pub key_group: u16,
+    /// Only present in DLS level 2 region headers.
+    pub layer: Option<u16>,
+    pub sample: Option<WaveSample>,
+    pub wave_link: WaveLink,
+    pub articulators: Vec<Articulator>,
+}
+
+impl Region {
+    pub fn from_chunk(chunk: &Chunk) -> RiffResult<Region> {
+        let mut result = Region {
+            articulators: read_articulators(chunk)?,
+            ..Region::default()
+        };
+        let mut has_header = false;
+        let mut has_link = false;
+        for child in chunk.iter()? {
+            let child = child?;
+            match child.id()?.as_bytes() {
+                RGNH_ID => {
+                    result
+                        .read_header(child.content()?)
+                        .map_err(|e| child.locate(e))?;
+                    has_header = true;
+                }
+                WSMP_ID => {
+                    let sample = WaveSample::from_bytes(child.content()?);
+                    result.sample = Some(sample.map_err(|e| child.locate(e))?);
+                }
+                WLNK_ID => {
+                    let wave_link = WaveLink::from_bytes(child.content()?);
+                    result.wave_link = wave_link.map_err(|e| child.locate(e))?;
+                    has_link = true;
+                }
+                _ => {}
+            }
+        }
+        if !has_header || !has_link {
+            return Err(chunk.locate(RiffError::malformed("region without an rgnh or wlnk chunk")));
+        }
+        Ok(result)
+    }
+
+    fn read_header(&mut self, data: &[u8]) -> RiffResult<()> {
+        self.key_range = (read_u16(data, 0)?, read_u16(data, 2)?);
+        self.velocity_range = (read_u16(data, 4)?, read_u16(data, 6)?);
+        self.options = read_u16(data, 8)?;
+        self.key_group = read_u16(data, 10)?;
+        self.layer = read_u16(data, 12).ok();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Instrument {
+    pub name: Option<String>,
+    /// The MIDI bank, with `F_INSTRUMENT_DRUMS` set for drum kits.
+    pub bank: u32,
+    pub program: u32,
+    pub regions: Vec<Region>,
+    /// Global articulators, applying to every region without its own.
+    pub articulators: Vec<Articulator>,
+}
+
+impl Instrument {
+    pub fn from_chunk(chunk: &Chunk) -> RiffResult<Instrument> {
+        let mut result = Instrument {
+            name: read_name(chunk)?,
+            articulators: read_articulators(chunk)?,
+            ..Instrument::default()
+        };
+        for child in chunk.iter()? {
+            let child = child?;
+            match child.kind() {
+                ChunkKind::List if child.chunk_type()?.as_bytes() == LRGN_ID => {
+                    for region in child.iter()? {
+                        let region = region?;
+                        if region.kind() == ChunkKind::List {
+                            if let RGN_ID | RGN2_ID = region.chunk_type()?.as_bytes() {
+                                result.regions.push(Region::from_chunk(&region)?);
+                            }
+                        }
+                    }
+                }
+                _ if child.id()?.as_bytes() == INSH_ID => {
+                    let data = child.content()?;
+                    result.bank = read_u32(data, 4).map_err(|e| child.locate(e))?;
+                    result.program = read_u32(data, 8).map_err(|e| child.locate(e))?;
+                }
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn is_drum(&self) -> bool {
+        self.bank & F_INSTRUMENT_DRUMS != 0
+    }
+}
+
+/// A `LIST wave` of the wave pool. Its slices borrow from the underlying `Chunk`.
+#[derive(Debug, Clone)]
+pub struct Wave<'a> {
+    pub name: Option<String>,
+    /// Offset of the wave relative to the start of the wave pool data, as used by `ptbl`.
+    pub offset: u32,
+    pub format: WaveFormat,
+    pub sample: Option<WaveSample>,
+    pub data: &'a [u8],
+}
+
+impl<'a> Wave<'a> {
+    pub fn from_chunk(chunk: &Chunk<'a>, offset: u32) -> RiffResult<Wave<'a>> {
+        let mut format = None;
+        let mut sample = None;
+        let mut data = None;
+        for child in chunk.iter()? {
+            let child = child?;
+            match child.id()?.as_bytes() {
+                FMT_ID => {
+                    let fmt = WaveFormat::from_bytes(child.content()?);
+                    format = Some(fmt.map_err(|e| child.locate(e))?);
+                }
+                WSMP_ID => {
+                    let wsmp = WaveSample::from_bytes(child.content()?);
+                    sample = Some(wsmp.map_err(|e| child.locate(e))?);
+                }
+                DATA_ID => data = Some(child.content()?),
+                _ => {}
+            }
+        }
+        match (format, data) {
+            (Some(format), Some(data)) => Ok(Wave {
+                name: read_name(chunk)?,
+                offset,
+                format,
+                sample,
+                data,
+            }),
+            _ => Err(chunk.locate(RiffError::malformed("wave without a fmt or data chunk"))),
+        }
+    }
+}
+
+/// A parsed `RIFF DLS ` collection.
+#[derive(Debug, Clone, Default)]
+pub struct Dls<'a> {
+    pub name: Option<String>,
+    /// The `vers` chunk as `(most significant, least significant)` double words.
+    pub version: Option<(u32, u32)>,
+    /// The instrument count declared by `colh`.
+    pub instrument_count: u32,
+    pub instruments: Vec<Instrument>,
+    /// Offsets of the waves of the pool, indexed by `WaveLink::table_index`.
+    pub pool_table: Vec<u32>,
+    pub waves: Vec<Wave<'a>>,
+}
+
+impl<'a> Dls<'a> {
+    pub fn from_chunk(chunk: &Chunk<'a>) -> RiffResult<Dls<'a>> {
+        chunk.expect_form_type(DLS_ID)?;
+        let mut result = Dls {
+            name: read_name(chunk)?,
+            ..Dls::default()
+        };
+        let mut has_collection_header = false;
+        for child in chunk.iter()? {
+            let child = child?;
+            if child.kind() == ChunkKind::List {
+                match child.chunk_type()?.as_bytes() {
+                    LINS_ID => {
+                        for instrument in child.iter()? {
+                            let instrument = instrument?;
+                            if instrument.is_list_of(INS_ID)? {
+                                result
+                                    .instruments
+                                    .push(Instrument::from_chunk(&instrument)?);
+                            }
+                        }
+                    }
+                    WVPL_ID => {
+                        let mut offset = 0;
+                        for wave in child.iter()? {
+                            let wave = wave?;
+                            if wave.is_list_of(WAVE_ID)? {
+                                result.waves.push(Wave::from_chunk(&wave, offset)?);
+                            }
+                            let len = wave.payload_len()?;
+                            offset += 8 + len + len % 2;
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            result.read_child(&child).map_err(|e| child.locate(e))?;
+            if child.id()?.as_bytes() == COLH_ID {
+                has_collection_header = true;
+            }
+        }
+        if !has_collection_header {
+            return Err(chunk.locate(RiffError::malformed("missing colh chunk")));
+        }
+        Ok(result)
+    }
+
+    fn read_child(&mut self, child: &Chunk) -> RiffResult<()> {
+        let data = child.content()?;
+        match child.id()?.as_bytes() {
+            VERS_ID => self.version = Some((read_u32(data, 0)?, read_u32(data, 4)?)),
+            COLH_ID => self.instrument_count = read_u32(data, 0)?,
+            PTBL_ID => {
+                let size = read_u32(data, 0)? as usize;
+                let count = read_u32(data, 4)? as usize;
+                self.pool_table = (0..count)
+                    .map(|idx| read_u32(data, size + idx * 4))
+                    .collect::<RiffResult<_>>()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The wave played by `region`, resolved through the pool table.
+    pub fn wave(&self, region: &Region) -> Option<&Wave<'a>> {
+        let offset = *self.pool_table.get(region.wave_link.table_index as usize)?;
+        self.waves.iter().find(|wave| wave.offset == offset)
+    }
+}
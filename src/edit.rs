@@ -0,0 +1,242 @@
+use crate::{error::RiffResult, query::Query, writer::ChunkContents, Chunk, ChunkKind, RiffError};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+/// Records structural edits of a parsed file, then writes the edited file out.
+///
+/// Chunks are designated by path queries, as accepted by `Chunk::select`, which are resolved
+/// against the original file when each edit is recorded. When writing, the payloads of chunks
+/// that were not replaced are streamed straight from the original buffer and the size of every
+/// ancestor of an edited chunk is recomputed.
+///
+/// Within a list, removals and replacements apply first, then moves, then insertions, in the
+/// order they were recorded. Indexes count the children left at that point. An edit that would
+/// be lost fails instead: inserting into or moving a chunk that an earlier edit removed or
+/// replaced, along with removing or replacing a chunk that earlier edits insert into or move.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{edit::Editor, writer::ChunkContents, Chunk, FourCC};
+/// let bytes = b"RIFF\x1a\0\0\0smplLIST\x0e\0\0\0tst1test\x02\0\0\0ab";
+/// let chunk = Chunk::from_bytes(bytes).unwrap();
+/// let mut editor = Editor::new(chunk);
+/// let name = ChunkContents::RawData(FourCC::new(b"INAM").unwrap(), b"name".to_vec().into());
+/// editor.remove("LIST:tst1/test").unwrap();
+/// editor.append("LIST:tst1", name).unwrap();
+/// let edited = editor.to_bytes().unwrap();
+/// assert_eq!(edited, b"RIFF\x1c\0\0\0smplLIST\x10\0\0\0tst1INAM\x04\0\0\0name");
+/// ```
+#[derive(Debug)]
+pub struct Editor<'a> {
+    root: Chunk<'a>,
+    removed: HashSet<usize>,
+    replaced: HashMap<usize, ChunkContents<'a>>,
+    /// Children to move, keyed by the offset of their parent.
+    moves: HashMap<usize, Vec<(usize, usize)>>,
+    /// Chunks to insert, keyed by the offset of their parent, at an index or at the end.
+    inserts: HashMap<usize, Vec<(Option<usize>, ChunkContents<'a>)>>,
+}
+
+impl<'a> Editor<'a> {
+    pub fn new(root: Chunk<'a>) -> Editor<'a> {
+        Editor {
+            root,
+            removed: HashSet::new(),
+            replaced: HashMap::new(),
+            moves: HashMap::new(),
+            inserts: HashMap::new(),
+        }
+    }
+
+    /// Removes every chunk matching `query`, which may match none.
+    pub fn remove(&mut self, query: &str) -> RiffResult<&mut Self> {
+        let found = Query::parse(query)?.select(&self.root)?;
+        for chunk in &found {
+            self.check_no_pending(chunk)?;
+        }
+        for chunk in found {
+            self.removed.insert(chunk.offset());
+        }
+        Ok(self)
+    }
+
+    /// Replaces every chunk matching `query` by `contents`.
+    pub fn replace(&mut self, query: &str, contents: ChunkContents<'a>) -> RiffResult<&mut Self> {
+        let found = self.select(query)?;
+        for chunk in &found {
+            self.check_no_pending(chunk)?;
+        }
+        for chunk in found {
+            self.replaced.insert(chunk.offset(), contents.clone());
+        }
+        Ok(self)
+    }
+
+    /// Inserts `contents` at `index` among the children of every container matching `query`.
+    pub fn insert(
+        &mut self,
+        query: &str,
+        index: usize,
+        contents: ChunkContents<'a>,
+    ) -> RiffResult<&mut Self> {
+        self.insert_at(query, Some(index), contents)
+    }
+
+    /// Inserts `contents` after the children of every container matching `query`.
+    pub fn append(&mut self, query: &str, contents: ChunkContents<'a>) -> RiffResult<&mut Self> {
+        self.insert_at(query, None, contents)
+    }
+
+    /// Moves every chunk matching `query` to `index` among its siblings.
+    pub fn move_to(&mut self, query: &str, index: usize) -> RiffResult<&mut Self> {
+        let found = self.select(query)?;
+        let mut parents = Vec::new();
+        for chunk in &found {
+            let parent = chunk.parent().ok_or_else(|| RiffError::MalformedChunk {
+                location: chunk.location(),
+                reason: "the root chunk cannot be moved",
+            })?;
+            self.check_kept(chunk)?;
+            parents.push(parent);
+        }
+        for (chunk, parent) in found.into_iter().zip(parents) {
+            self.moves
+                .entry(parent.offset())
+                .or_default()
+                .push((chunk.offset(), index));
+        }
+        Ok(self)
+    }
+
+    /// The edited file, borrowing every payload that was not replaced.
+    pub fn to_contents(&self) -> RiffResult<ChunkContents<'a>> {
+        self.build(&self.root)?
+            .ok_or_else(|| RiffError::MalformedChunk {
+                location: self.root.location(),
+                reason: "the root chunk was removed",
+            })
+    }
+
+    /// Writes the edited file and returns the number of bytes written.
+    pub fn write<W>(&self, writer: &mut W) -> RiffResult<u64>
+    where
+        W: Write,
+    {
+        self.to_contents()?.write(writer)
+    }
+
+    pub fn to_bytes(&self) -> RiffResult<Vec<u8>> {
+        self.to_contents()?.to_bytes()
+    }
+
+    /// The chunks matching `query`, which must match at least one.
+    fn select(&self, query: &str) -> RiffResult<Vec<Chunk<'a>>> {
+        let found = Query::parse(query)?.select(&self.root)?;
+        if found.is_empty() {
+            return Err(RiffError::InvalidQuery {
+                query: query.to_string(),
+                reason: "query matches no chunk",
+            });
+        }
+        Ok(found)
+    }
+
+    fn insert_at(
+        &mut self,
+        query: &str,
+        index: Option<usize>,
+        contents: ChunkContents<'a>,
+    ) -> RiffResult<&mut Self> {
+        let found = self.select(query)?;
+        if let Some(chunk) = found.iter().find(|chunk| chunk.kind() == ChunkKind::Raw) {
+            return Err(RiffError::MalformedChunk {
+                location: chunk.location(),
+                reason: "chunks can only be inserted into a container",
+            });
+        }
+        for chunk in &found {
+            self.check_kept(chunk)?;
+        }
+        for chunk in found {
+            self.inserts
+                .entry(chunk.offset())
+                .or_default()
+                .push((index, contents.clone()));
+        }
+        Ok(self)
+    }
+
+    /// Fails if an earlier edit removed or replaced `chunk` or one of the chunks holding it.
+    fn check_kept(&self, chunk: &Chunk<'a>) -> RiffResult<()> {
+        let mut current = Some(chunk.clone());
+        while let Some(ancestor) = current {
+            let offset = ancestor.offset();
+            if self.removed.contains(&offset) || self.replaced.contains_key(&offset) {
+                return Err(RiffError::MalformedChunk {
+                    location: chunk.location(),
+                    reason: "chunk was removed or replaced by an earlier edit",
+                });
+            }
+            current = ancestor.parent();
+        }
+        Ok(())
+    }
+
+    /// Fails if an earlier edit inserts into or moves `chunk` or a chunk it holds.
+    fn check_no_pending(&self, chunk: &Chunk<'a>) -> RiffResult<()> {
+        let range = chunk.offset()..chunk.offset() + chunk.as_bytes().len();
+        let moved = self.moves.values().flatten().map(|(moved, _)| moved);
+        if self
+            .inserts
+            .keys()
+            .chain(moved)
+            .any(|offset| range.contains(offset))
+        {
+            return Err(RiffError::MalformedChunk {
+                location: chunk.location(),
+                reason: "chunk holds insertions or moves recorded earlier",
+            });
+        }
+        Ok(())
+    }
+
+    /// Builds the edited version of `chunk`, or `None` if it was removed.
+    fn build(&self, chunk: &Chunk<'a>) -> RiffResult<Option<ChunkContents<'a>>> {
+        let offset = chunk.offset();
+        if self.removed.contains(&offset) {
+            return Ok(None);
+        }
+        if let Some(contents) = self.replaced.get(&offset) {
+            return Ok(Some(contents.clone()));
+        }
+        let id = chunk.id()?;
+        if chunk.kind() == ChunkKind::Raw {
+            let payload = Cow::Borrowed(chunk.content()?);
+            return Ok(Some(ChunkContents::RawData(id, payload)));
+        }
+        let mut children = Vec::new();
+        for child in chunk.iter()? {
+            let child = child?;
+            if let Some(contents) = self.build(&child)? {
+                children.push((Some(child.offset()), contents));
+            }
+        }
+        for (moved, index) in self.moves.get(&offset).into_iter().flatten() {
+            if let Some(position) = children.iter().position(|(o, _)| *o == Some(*moved)) {
+                let child = children.remove(position);
+                children.insert((*index).min(children.len()), child);
+            }
+        }
+        for (index, contents) in self.inserts.get(&offset).into_iter().flatten() {
+            let index = index.unwrap_or(children.len()).min(children.len());
+            children.insert(index, (None, contents.clone()));
+        }
+        let children = children.into_iter().map(|(_, contents)| contents).collect();
+        Ok(Some(match chunk.kind() {
+            ChunkKind::List => ChunkContents::Children(id, chunk.chunk_type()?, children),
+            _ => ChunkContents::ChildrenNoType(id, children),
+        }))
+    }
+}
@@ -1,22 +1,184 @@
+use crate::FourCC;
+use std::fmt;
+
+/// Where in a file an error occurred.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Location {
+    /// Absolute offset in bytes from the start of the parsed buffer.
+    pub offset: usize,
+    /// Path of the chunk being parsed, such as `RIFF:WAVE/LIST:INFO/ICMT`.
+    /// Empty when the error was raised outside of any chunk.
+    pub path: String,
+}
+
+impl Location {
+    pub fn new(offset: usize, path: String) -> Location {
+        Location { offset, path }
+    }
+
+    /// Whether the location has not been filled in.
+    pub fn is_unknown(&self) -> bool {
+        self.path.is_empty() && self.offset == 0
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "offset {}", self.offset)
+        } else {
+            write!(f, "offset {} ({})", self.offset, self.path)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum RiffError {
-    InsufficientBytes,
-    Other(Box<dyn std::error::Error>),
+    /// Fewer bytes remain than needed for a chunk header, or for the form type of a list.
+    TruncatedHeader {
+        location: Location,
+        needed: usize,
+        available: usize,
+    },
+    /// The size field of a chunk extends past the end of its parent or of the buffer.
+    PayloadOverrunsParent {
+        location: Location,
+        payload_len: u32,
+        available: usize,
+    },
+    /// An identifier is not made of exactly four bytes.
+    InvalidFourCC {
+        location: Location,
+        bytes: Vec<u8>,
+    },
+    /// A chunk does not carry the identifier or form type expected by a reader.
+    UnexpectedContainerType {
+        location: Location,
+        found: FourCC,
+    },
+    /// The payload of a chunk does not follow the layout of its format.
+    MalformedChunk {
+        location: Location,
+        reason: &'static str,
+    },
+    /// A size does not fit in the integer type of the field holding it.
+    SizeOverflow {
+        location: Location,
+    },
+    /// A path query does not follow the query syntax.
+    InvalidQuery {
+        query: String,
+        reason: &'static str,
+    },
+    Io(std::io::Error),
 }
 
-macro_rules! impl_error {
-    ( $error : ty ) => {
-        impl From<$error> for RiffError {
-            /// Performs the conversion.
-            fn from(v: $error) -> Self {
-                RiffError::Other(Box::new(v))
+impl RiffError {
+    /// The location at which the error occurred, if it is tied to one.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            RiffError::TruncatedHeader { location, .. }
+            | RiffError::PayloadOverrunsParent { location, .. }
+            | RiffError::InvalidFourCC { location, .. }
+            | RiffError::UnexpectedContainerType { location, .. }
+            | RiffError::MalformedChunk { location, .. }
+            | RiffError::SizeOverflow { location } => Some(location),
+            RiffError::InvalidQuery { .. } | RiffError::Io(_) => None,
+        }
+    }
+
+    /// Fills in the location of an error raised without knowledge of where it happened, such
+    /// as while decoding the payload of a chunk from a bare `&[u8]`.
+    pub fn or_location(mut self, with: impl FnOnce() -> Location) -> RiffError {
+        match &mut self {
+            RiffError::TruncatedHeader { location, .. }
+            | RiffError::PayloadOverrunsParent { location, .. }
+            | RiffError::InvalidFourCC { location, .. }
+            | RiffError::UnexpectedContainerType { location, .. }
+            | RiffError::MalformedChunk { location, .. }
+            | RiffError::SizeOverflow { location } => {
+                if location.is_unknown() {
+                    *location = with();
+                }
             }
+            RiffError::InvalidQuery { .. } | RiffError::Io(_) => {}
         }
-    };
+        self
+    }
+
+    /// Shorthand for a `MalformedChunk` whose location is filled in later.
+    pub fn malformed(reason: &'static str) -> RiffError {
+        RiffError::MalformedChunk {
+            location: Location::default(),
+            reason,
+        }
+    }
 }
 
-impl_error!(std::array::TryFromSliceError);
-impl_error!(std::io::Error);
+impl fmt::Display for RiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiffError::TruncatedHeader {
+                location,
+                needed,
+                available,
+            } => write!(
+                f,
+                "truncated header at {}: needed {} bytes but only {} are available",
+                location, needed, available
+            ),
+            RiffError::PayloadOverrunsParent {
+                location,
+                payload_len,
+                available,
+            } => write!(
+                f,
+                "payload of {} bytes overruns its parent at {}: only {} bytes are available",
+                payload_len, location, available
+            ),
+            RiffError::InvalidFourCC { location, bytes } => {
+                write!(f, "invalid FourCC {:?} at {}", bytes, location)
+            }
+            RiffError::UnexpectedContainerType { location, found } => {
+                let found = String::from_utf8_lossy(found.as_bytes());
+                write!(f, "unexpected container type {:?} at {}", found, location)
+            }
+            RiffError::MalformedChunk { location, reason } => {
+                write!(f, "malformed chunk at {}: {}", location, reason)
+            }
+            RiffError::SizeOverflow { location } => write!(f, "size overflow at {}", location),
+            RiffError::InvalidQuery { query, reason } => {
+                write!(f, "invalid query {:?}: {}", query, reason)
+            }
+            RiffError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RiffError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RiffError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RiffError {
+    /// Performs the conversion.
+    fn from(v: std::io::Error) -> Self {
+        RiffError::Io(v)
+    }
+}
+
+impl From<std::num::TryFromIntError> for RiffError {
+    /// Performs the conversion.
+    fn from(_: std::num::TryFromIntError) -> Self {
+        RiffError::SizeOverflow {
+            location: Location::default(),
+        }
+    }
+}
 
 /// A convenient `Result` type.
 pub type RiffResult<T> = Result<T, RiffError>;
@@ -1,33 +1,89 @@
-use crate::{error::RiffResult, RiffError};
+use crate::{
+    error::{Location, RiffResult},
+    RiffError,
+};
 use std::convert::{TryFrom, TryInto};
-
-#[derive(Debug, Clone)]
-pub struct FourCC {
-    data: [u8; 4],
-}
+use std::fmt;
+use std::str::FromStr;
 
 /// Represents the ASCII identifier of a chunk.
 ///
+/// Identifiers read from a file are taken as they are, since damaged or unusual files may hold
+/// any bytes. Identifiers built by hand can be checked to be printable ASCII with
+/// `FourCC::new_checked` or `FourCC::padded`, and `FourCC::from_array` performs that check at
+/// compile time.
+///
 /// # Example
 ///
 /// ```rust
 /// use riffu::FourCC;
-/// let good = FourCC::new(b"1234");
-/// // let bad = FourCC::new(b"12345"); // won't compile
+/// const WAVE: FourCC = FourCC::from_array(*b"WAVE");
+/// let good = FourCC::new(b"1234").unwrap();
+/// assert!(FourCC::new(b"12345").is_err());
+/// assert_eq!("avi".parse::<FourCC>().unwrap(), FourCC::from_array(*b"avi "));
+/// match FourCC::new(b"WAVE").unwrap() {
+///     WAVE => {}
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(FourCC::new_unchecked(*b"ab\x00\xff").to_string(), "ab\\x00\\xff");
 /// ```
-///
-/// # NOTE
-///
-/// 1. AFAIK, the only valid identifier is in ASCII.
-///    I am not entirely sure what all the possible value ASCII can take.
-///    So we need to enforce correctness on that front too.
-/// 2. Are there other conversions that we are missing out on?
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FourCC {
+    data: [u8; 4],
+}
+
 impl FourCC {
+    /// Takes the 4 bytes of `data`, whatever they are.
     pub fn new(data: &[u8]) -> RiffResult<FourCC> {
-        let data = data.try_into()?;
+        let data = data.try_into().map_err(|_| invalid(data))?;
         Ok(FourCC { data })
     }
 
+    /// Takes the 4 bytes of `data`, which must be printable ASCII.
+    pub fn new_checked(data: &[u8]) -> RiffResult<FourCC> {
+        let result = FourCC::new(data)?;
+        if !result.is_printable() {
+            return Err(invalid(data));
+        }
+        Ok(result)
+    }
+
+    /// Takes `data`, panicking if it is not printable ASCII.
+    ///
+    /// When used to define a constant, the check happens at compile time.
+    pub const fn from_array(data: [u8; 4]) -> FourCC {
+        let mut i = 0;
+        while i < 4 {
+            assert!(
+                data[i] >= 0x20 && data[i] < 0x7f,
+                "FourCC must be printable ASCII"
+            );
+            i += 1;
+        }
+        FourCC { data }
+    }
+
+    /// Takes `data` without checking it.
+    pub const fn new_unchecked(data: [u8; 4]) -> FourCC {
+        FourCC { data }
+    }
+
+    /// Pads `value`, of 1 to 4 printable ASCII characters, with trailing spaces.
+    pub fn padded(value: &str) -> RiffResult<FourCC> {
+        let bytes = value.as_bytes();
+        if bytes.is_empty() || bytes.len() > 4 {
+            return Err(invalid(bytes));
+        }
+        let mut data = [b' '; 4];
+        data[..bytes.len()].copy_from_slice(bytes);
+        FourCC::new_checked(&data)
+    }
+
+    /// Whether every byte is printable ASCII.
+    pub fn is_printable(&self) -> bool {
+        is_printable(&self.data)
+    }
+
     /// View `&self` struct as a `&[u8]`.
     pub fn as_bytes(&self) -> &[u8; 4] {
         &self.data
@@ -39,6 +95,41 @@ impl FourCC {
     }
 }
 
+/// Whether `id` looks like an identifier rather than arbitrary bytes.
+pub(crate) fn is_printable(id: &[u8]) -> bool {
+    id.iter().all(|b| (0x20..0x7f).contains(b))
+}
+
+fn invalid(bytes: &[u8]) -> RiffError {
+    RiffError::InvalidFourCC {
+        location: Location::default(),
+        bytes: bytes.to_vec(),
+    }
+}
+
+/// Prints the identifier as is, escaping the bytes that are not printable ASCII as `\xNN`.
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &byte in &self.data {
+            if is_printable(&[byte]) && byte != b'\\' {
+                write!(f, "{}", byte as char)?;
+            } else {
+                write!(f, "\\x{:02x}", byte)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses 1 to 4 printable ASCII characters, padded with trailing spaces.
+impl FromStr for FourCC {
+    type Err = RiffError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        FourCC::padded(value)
+    }
+}
+
 /// A `&[u8]` can be converted to a `FourCC`.
 impl TryFrom<&[u8]> for FourCC {
     type Error = RiffError;
@@ -51,9 +142,7 @@ impl TryFrom<&[u8]> for FourCC {
     /// let test: FourCC = buffer.try_into().unwrap();
     /// ```
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        Ok(FourCC {
-            data: value.try_into()?,
-        })
+        FourCC::new(value)
     }
 }
 
@@ -71,3 +160,49 @@ impl TryFrom<&str> for FourCC {
         value.as_bytes().try_into()
     }
 }
+
+/// Serializes as the string printed by `Display`, so that any identifier round-trips.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FourCC {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FourCC {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let text = String::deserialize(deserializer)?;
+        unescape(&text)
+            .map(FourCC::new_unchecked)
+            .ok_or_else(|| D::Error::custom(format!("invalid FourCC {:?}", text)))
+    }
+}
+
+/// Reverses the escaping done by `Display`.
+#[cfg(feature = "serde")]
+fn unescape(text: &str) -> Option<[u8; 4]> {
+    let mut result = Vec::with_capacity(4);
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(escaped) = rest.strip_prefix("\\x") {
+            result.push(u8::from_str_radix(escaped.get(0..2)?, 16).ok()?);
+            rest = &escaped[2..];
+        } else {
+            let c = rest.chars().next()?;
+            if !c.is_ascii() || c == '\\' {
+                return None;
+            }
+            result.push(c as u8);
+            rest = &rest[1..];
+        }
+    }
+    result.try_into().ok()
+}
@@ -0,0 +1,206 @@
+use crate::{
+    error::{Location, RiffResult},
+    Chunk, ChunkKind, RiffError,
+};
+use memmap::MmapMut;
+use std::fs::OpenOptions;
+use std::path::Path;
+
+const JUNK_ID: &[u8; 4] = b"JUNK";
+
+/// A RIFF file mapped for writing, for edits that keep every chunk at its place.
+///
+/// Chunks are designated by their absolute offset, as given by `Chunk::offset`. Every edit
+/// checks that the chunk exists, and that its parent still parses afterwards, restoring the
+/// previous bytes otherwise.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use riffu::RiffMut;
+/// let mut file = RiffMut::from_path("recording.wav").unwrap();
+/// let offset = file.as_chunk().unwrap().select_first("LIST:INFO").unwrap().unwrap().offset();
+/// file.convert_to_junk(offset).unwrap();
+/// file.flush().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RiffMut {
+    inner: MmapMut,
+}
+
+/// What an edit needs to know about the chunk it modifies.
+struct Target {
+    offset: usize,
+    /// The number of bytes of the chunk, including its header and pad byte.
+    len: usize,
+    payload_len: usize,
+    kind: ChunkKind,
+    location: Location,
+    parent: Option<usize>,
+}
+
+impl RiffMut {
+    pub fn from_path<P>(path: P) -> RiffResult<RiffMut>
+    where
+        P: AsRef<Path>,
+    {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let inner = unsafe { MmapMut::map_mut(&file)? };
+        Ok(RiffMut { inner })
+    }
+
+    pub fn as_chunk(&self) -> RiffResult<Chunk<'_>> {
+        Chunk::from_bytes(&self.inner)
+    }
+
+    /// Replaces the payload of the chunk at `offset` by `payload`, which must have the same
+    /// length.
+    pub fn overwrite(&mut self, offset: usize, payload: &[u8]) -> RiffResult<()> {
+        let target = self.find(offset)?;
+        if target.kind != ChunkKind::Raw {
+            return Err(malformed(
+                target.location,
+                "only raw chunks can be overwritten",
+            ));
+        }
+        if payload.len() != target.payload_len {
+            return Err(malformed(
+                target.location,
+                "payload length differs from the chunk",
+            ));
+        }
+        let start = target.offset + 8;
+        self.edit(&target, |data| {
+            data[start..start + payload.len()].copy_from_slice(payload)
+        })
+    }
+
+    /// Turns the chunk at `offset`, along with everything it holds, into a zeroed `JUNK` chunk.
+    pub fn convert_to_junk(&mut self, offset: usize) -> RiffResult<()> {
+        let target = self.find(offset)?;
+        if target.parent.is_none() {
+            return Err(malformed(
+                target.location,
+                "the root chunk cannot become JUNK",
+            ));
+        }
+        self.edit(&target, |data| {
+            let chunk = &mut data[target.offset..target.offset + target.len];
+            chunk[0..4].copy_from_slice(JUNK_ID);
+            for byte in &mut chunk[8..] {
+                *byte = 0;
+            }
+        })
+    }
+
+    /// Replaces the payload of the chunk at `offset` by the shorter `payload`, and fills the
+    /// space freed with a `JUNK` chunk so that no other chunk moves.
+    pub fn shrink(&mut self, offset: usize, payload: &[u8]) -> RiffResult<()> {
+        let target = self.find(offset)?;
+        if target.kind != ChunkKind::Raw {
+            return Err(malformed(target.location, "only raw chunks can be shrunk"));
+        }
+        let new_len = 8 + payload.len() + payload.len() % 2;
+        if new_len > target.len {
+            return Err(malformed(
+                target.location,
+                "payload is longer than the chunk",
+            ));
+        }
+        let freed = target.len - new_len;
+        if freed != 0 && freed < 8 {
+            return Err(malformed(
+                target.location,
+                "freed space is too small for a JUNK chunk",
+            ));
+        }
+        self.edit(&target, |data| {
+            let chunk = &mut data[target.offset..target.offset + target.len];
+            chunk[4..8].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+            chunk[8..8 + payload.len()].copy_from_slice(payload);
+            for byte in &mut chunk[8 + payload.len()..] {
+                *byte = 0;
+            }
+            if freed != 0 {
+                let junk = &mut chunk[new_len..];
+                junk[0..4].copy_from_slice(JUNK_ID);
+                junk[4..8].copy_from_slice(&((freed - 8) as u32).to_le_bytes());
+            }
+        })
+    }
+
+    /// Writes the edits made so far to the file.
+    pub fn flush(&self) -> RiffResult<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    /// Finds the chunk starting exactly at `offset`.
+    fn find(&self, offset: usize) -> RiffResult<Target> {
+        let mut walk = self.as_chunk()?.walk();
+        while let Some(entry) = walk.next() {
+            let chunk = entry?.chunk;
+            let len = chunk.as_bytes().len();
+            if chunk.offset() == offset {
+                return Ok(Target {
+                    offset,
+                    len,
+                    payload_len: chunk.payload_len()? as usize,
+                    kind: chunk.kind(),
+                    location: chunk.location(),
+                    parent: chunk.parent().map(|parent| parent.offset()),
+                });
+            }
+            if !(chunk.offset()..chunk.offset() + len).contains(&offset) {
+                walk.skip_children();
+            }
+        }
+        Err(malformed(
+            Location::new(offset, String::new()),
+            "no chunk starts at this offset",
+        ))
+    }
+
+    /// Applies `edit` to the buffer, then checks that the parent of `target` and the root still
+    /// parse, restoring the chunk if they do not.
+    fn edit<F>(&mut self, target: &Target, edit: F) -> RiffResult<()>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let range = target.offset..target.offset + target.len;
+        let backup = self.inner[range.clone()].to_vec();
+        edit(&mut self.inner);
+        if let Err(err) = self.check(target.parent.unwrap_or(0)) {
+            self.inner[range].copy_from_slice(&backup);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Checks that the chunk at `offset` and its children still parse.
+    fn check(&self, offset: usize) -> RiffResult<()> {
+        let root = self.as_chunk()?;
+        let mut walk = root.walk();
+        while let Some(entry) = walk.next() {
+            let chunk = entry?.chunk;
+            if chunk.offset() == offset {
+                for child in chunk.iter()? {
+                    child?;
+                }
+                return Ok(());
+            }
+            let end = chunk.offset() + chunk.as_bytes().len();
+            if !(chunk.offset()..end).contains(&offset) {
+                walk.skip_children();
+            }
+        }
+        Err(malformed(
+            root.location(),
+            "edited chunk could not be found",
+        ))
+    }
+}
+
+fn malformed(location: Location, reason: &'static str) -> RiffError {
+    RiffError::MalformedChunk { location, reason }
+}
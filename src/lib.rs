@@ -1,8 +1,34 @@
+pub mod avi;
+pub mod carve;
+pub mod codec;
 pub mod constants;
+pub mod diff;
+pub mod dls;
+pub mod edit;
 pub mod error;
 pub mod fourcc;
+pub mod inplace;
+pub mod options;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod query;
+pub mod registry;
+pub mod repair;
 pub mod riff;
+pub mod sf2;
+pub mod shared;
+pub mod tree;
+pub mod validate;
+pub mod walk;
+pub mod webp;
+pub mod writer;
 
+pub use codec::ChunkCodec;
 pub use error::RiffError;
 pub use fourcc::FourCC;
-pub use riff::{Chunk, ChunkIter, Riff};
+pub use inplace::RiffMut;
+pub use options::ParseOptions;
+pub use riff::{Chunk, ChunkIter, ChunkKind, Riff};
+
+#[cfg(feature = "derive")]
+pub use riffu_derive::ChunkCodec;
@@ -0,0 +1,190 @@
+use crate::{error::Location, registry::Registry, ChunkKind, FourCC};
+use std::fmt;
+use std::sync::Mutex;
+
+/// How a parser reacts to files that do not follow the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Reject any chunk whose layout does not fit its parent.
+    Strict,
+    /// Recover from common mistakes and record a `Warning` for each recovery.
+    Lenient,
+}
+
+/// Options controlling how chunks are parsed.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{options::ParseOptions, Chunk};
+/// // The size field claims 4 bytes of payload but only 2 follow.
+/// let bytes = b"test\x04\0\0\0ab";
+/// assert!(Chunk::from_bytes(bytes).is_err());
+/// let chunk = Chunk::from_bytes_with_options(bytes, ParseOptions::lenient()).unwrap();
+/// assert_eq!(chunk.content().unwrap(), b"ab");
+/// assert_eq!(chunk.warnings().len(), 1);
+/// ```
+///
+/// Besides `RIFF`, `LIST` and `seqt`, chunks with other identifiers can be parsed as containers.
+///
+/// ```rust
+/// use riffu::{options::ParseOptions, Chunk, ChunkKind, FourCC};
+/// let bytes = b"RIFF\x18\0\0\0smplGRP \x0c\0\0\0abcdtest\0\0\0\0";
+/// let group = FourCC::from_array(*b"GRP ");
+/// let options = ParseOptions::strict().with_list(group, None);
+/// let chunk = Chunk::from_bytes_with_options(bytes, options).unwrap();
+/// let group = chunk.select_first("GRP :abcd").unwrap().unwrap();
+/// assert_eq!(group.kind(), ChunkKind::List);
+/// assert_eq!(group.iter().unwrap().count(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub mode: ParseMode,
+    /// Identifiers parsed as containers, with the form type they are restricted to if any.
+    containers: Vec<(FourCC, Option<FourCC>, ChunkKind)>,
+    reject_trailing: bool,
+}
+
+impl ParseOptions {
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            mode: ParseMode::Strict,
+            containers: Vec::new(),
+            reject_trailing: false,
+        }
+    }
+
+    /// Clamps chunks to their parent, tolerates missing pad bytes and skips trailing junk.
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            mode: ParseMode::Lenient,
+            containers: Vec::new(),
+            reject_trailing: false,
+        }
+    }
+
+    pub fn is_lenient(&self) -> bool {
+        self.mode == ParseMode::Lenient
+    }
+
+    /// Makes `Riff::as_chunk` fail on bytes following the root chunk, instead of recording them
+    /// as a `TrailingBytes` warning.
+    pub fn reject_trailing(mut self) -> ParseOptions {
+        self.reject_trailing = true;
+        self
+    }
+
+    pub fn rejects_trailing(&self) -> bool {
+        self.reject_trailing
+    }
+
+    /// Parses chunks identified by `id` as lists, holding a form type followed by chunks.
+    ///
+    /// If `form_type` is given, this only applies within files of that form type.
+    pub fn with_list(mut self, id: FourCC, form_type: Option<FourCC>) -> ParseOptions {
+        self.containers.push((id, form_type, ChunkKind::List));
+        self
+    }
+
+    /// Parses chunks identified by `id` as sequences, holding chunks without a form type.
+    ///
+    /// If `form_type` is given, this only applies within files of that form type.
+    pub fn with_sequence(mut self, id: FourCC, form_type: Option<FourCC>) -> ParseOptions {
+        self.containers.push((id, form_type, ChunkKind::Seqt));
+        self
+    }
+
+    /// Parses the chunks that `registry` describes as lists or sequences as such, within the
+    /// form types they are registered for.
+    pub fn with_registry(mut self, registry: &Registry) -> ParseOptions {
+        self.containers.extend(registry.containers());
+        self
+    }
+
+    /// How to parse a chunk identified by `id` in a file of `form_type`, when `id` is not one
+    /// of the standard containers. Later registrations take precedence.
+    pub(crate) fn container_kind(&self, id: &[u8], form_type: Option<FourCC>) -> ChunkKind {
+        self.containers
+            .iter()
+            .rev()
+            .find(|(found, scope, _)| {
+                found.as_bytes() == id && scope.is_none_or(|scope| Some(scope) == form_type)
+            })
+            .map_or(ChunkKind::Raw, |(_, _, kind)| *kind)
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions::strict()
+    }
+}
+
+/// A recovery made while parsing in lenient mode, or bytes ignored after the root chunk of a
+/// `Riff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+    /// The size field of the chunk extended past its parent and was reduced to `available`.
+    SizeClamped { declared: u32, available: u32 },
+    /// The chunk has an odd size but its pad byte is missing.
+    MissingPadding,
+    /// Bytes at the end of a parent that do not form a chunk were ignored.
+    TrailingBytes { len: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub location: Location,
+    pub kind: WarningKind,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            WarningKind::SizeClamped {
+                declared,
+                available,
+            } => write!(
+                f,
+                "size of {} bytes clamped to {} at {}",
+                declared, available, self.location
+            ),
+            WarningKind::MissingPadding => write!(f, "missing pad byte at {}", self.location),
+            WarningKind::TrailingBytes { len } => {
+                write!(f, "{} trailing bytes ignored at {}", len, self.location)
+            }
+        }
+    }
+}
+
+/// State shared by every chunk parsed from the same buffer.
+#[derive(Debug, Default)]
+pub(crate) struct ParseContext {
+    pub(crate) options: ParseOptions,
+    warnings: Mutex<Vec<Warning>>,
+}
+
+impl ParseContext {
+    pub(crate) fn new(options: ParseOptions) -> ParseContext {
+        ParseContext {
+            options,
+            warnings: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Records `warning`, unless the same chunk was already recovered by an earlier pass.
+    pub(crate) fn warn(&self, location: Location, kind: WarningKind) {
+        let warning = Warning { location, kind };
+        let mut warnings = self.warnings.lock().unwrap_or_else(|err| err.into_inner());
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+
+    pub(crate) fn warnings(&self) -> Vec<Warning> {
+        self.warnings
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
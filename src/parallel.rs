@@ -0,0 +1,45 @@
+//! Parallel iteration over chunks with `rayon`, enabled by the `rayon` feature.
+//!
+//! Finding where a chunk starts means reading the size of the chunk before it, so the chunks
+//! are parsed in order before being handed out to the thread pool.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rayon::prelude::*;
+//! use riffu::Riff;
+//! let file = Riff::from_path("test_assets/sample.avi").unwrap();
+//! let chunk = file.as_chunk().unwrap();
+//! let raw: u32 = chunk
+//!     .par_walk()
+//!     .map(|entry| entry.unwrap())
+//!     .filter(|entry| !entry.is_container())
+//!     .map(|entry| entry.chunk.payload_len().unwrap())
+//!     .sum();
+//! assert_eq!(raw, 2004561);
+//! ```
+
+use crate::{error::RiffResult, shared::SharedChunk, walk::Entry, Chunk};
+use rayon::prelude::*;
+
+impl<'a> Chunk<'a> {
+    /// The chunks held by this one, as a parallel iterator.
+    pub fn par_iter(
+        &self,
+    ) -> RiffResult<impl IndexedParallelIterator<Item = RiffResult<Chunk<'a>>>> {
+        Ok(self.iter()?.collect::<Vec<_>>().into_par_iter())
+    }
+
+    /// This chunk and everything below it, as a parallel iterator yielding them in the order of
+    /// `walk`.
+    pub fn par_walk(&self) -> impl IndexedParallelIterator<Item = RiffResult<Entry<'a>>> {
+        self.walk().collect::<Vec<_>>().into_par_iter()
+    }
+}
+
+impl SharedChunk {
+    /// The chunks held by this one, as a parallel iterator.
+    pub fn par_children(&self) -> RiffResult<impl IndexedParallelIterator<Item = SharedChunk>> {
+        Ok(self.children()?.into_par_iter())
+    }
+}
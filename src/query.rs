@@ -0,0 +1,178 @@
+use crate::{error::RiffResult, Chunk, ChunkKind, RiffError};
+use std::str::FromStr;
+
+/// Matches an identifier or a form type, where `None` matches anything.
+type Pattern = Option<[u8; 4]>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Selects the children matching `id` and, for lists, `chunk_type`, optionally keeping
+    /// only the one at `index` among them.
+    Step {
+        id: Pattern,
+        chunk_type: Option<Pattern>,
+        index: Option<usize>,
+    },
+    /// Selects the chunk itself and all of its descendants.
+    Descendants,
+    /// Selects the chunk itself.
+    Current,
+}
+
+/// A compiled path query, selecting chunks below a starting chunk.
+///
+/// A query is a list of segments separated by `/`, each selecting among the children of the
+/// chunks selected so far:
+///
+/// - `fmt` selects the chunks with that identifier, padded with spaces to four bytes.
+/// - `LIST:strl` selects the lists with that identifier and form type.
+/// - `*` matches any identifier, and `LIST:*` or `*:strl` any form type or list identifier.
+/// - `[1]` keeps only the second chunk selected by a segment within each parent.
+/// - `**` selects the chunks found at any depth, including the starting chunk itself.
+/// - `.` selects the starting chunk itself, so that `.` alone designates the root.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::Chunk;
+/// let bytes = b"RIFF\x1a\0\0\0smplLIST\x0e\0\0\0tst1test\x02\0\0\0ab";
+/// let chunk = Chunk::from_bytes(bytes).unwrap();
+/// let found = chunk.select("LIST:tst1/test").unwrap();
+/// assert_eq!(found[0].content().unwrap(), b"ab");
+/// assert_eq!(chunk.select("**/test[0]").unwrap().len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> RiffResult<Query> {
+        let invalid = |reason| RiffError::InvalidQuery {
+            query: query.to_string(),
+            reason,
+        };
+        if query.is_empty() {
+            return Err(invalid("query is empty"));
+        }
+        let segments = query
+            .split('/')
+            .map(|segment| parse_segment(segment).map_err(invalid))
+            .collect::<RiffResult<_>>()?;
+        Ok(Query { segments })
+    }
+
+    /// The chunks below `chunk` matching the query, in the order they appear in the file.
+    pub fn select<'a>(&self, chunk: &Chunk<'a>) -> RiffResult<Vec<Chunk<'a>>> {
+        let mut result = Vec::new();
+        select_from(chunk, &self.segments, &mut result)?;
+        // `**` reaches a chunk once through each way of splitting the levels between segments.
+        result.sort_by_key(|chunk| chunk.offset());
+        result.dedup_by_key(|chunk| chunk.offset());
+        Ok(result)
+    }
+}
+
+impl FromStr for Query {
+    type Err = RiffError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+fn parse_segment(segment: &str) -> Result<Segment, &'static str> {
+    match segment {
+        "**" => return Ok(Segment::Descendants),
+        "." => return Ok(Segment::Current),
+        _ => {}
+    }
+    let (name, index) = match segment.find('[') {
+        Some(start) => {
+            let index = segment[start + 1..]
+                .strip_suffix(']')
+                .ok_or("index is not closed by `]`")?
+                .parse()
+                .map_err(|_| "index is not a number")?;
+            (&segment[..start], Some(index))
+        }
+        None => (segment, None),
+    };
+    let (id, chunk_type) = match name.find(':') {
+        Some(colon) => (&name[..colon], Some(parse_pattern(&name[colon + 1..])?)),
+        None => (name, None),
+    };
+    Ok(Segment::Step {
+        id: parse_pattern(id)?,
+        chunk_type,
+        index,
+    })
+}
+
+fn parse_pattern(pattern: &str) -> Result<Pattern, &'static str> {
+    match pattern {
+        "" => Err("segment is empty"),
+        "*" => Ok(None),
+        _ if pattern.len() > 4 => Err("identifier is longer than four bytes"),
+        _ => {
+            let mut result = [b' '; 4];
+            result[..pattern.len()].copy_from_slice(pattern.as_bytes());
+            Ok(Some(result))
+        }
+    }
+}
+
+fn matches(pattern: &Pattern, bytes: &[u8; 4]) -> bool {
+    pattern.is_none_or(|pattern| &pattern == bytes)
+}
+
+fn select_from<'a>(
+    chunk: &Chunk<'a>,
+    segments: &[Segment],
+    result: &mut Vec<Chunk<'a>>,
+) -> RiffResult<()> {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            result.push(chunk.clone());
+            return Ok(());
+        }
+    };
+    match segment {
+        Segment::Current => return select_from(chunk, rest, result),
+        Segment::Descendants => select_from(chunk, rest, result)?,
+        Segment::Step { .. } => {}
+    }
+    if chunk.kind() == ChunkKind::Raw {
+        return Ok(());
+    }
+    let mut count = 0;
+    for child in chunk.iter()? {
+        let child = child?;
+        match segment {
+            Segment::Current => {}
+            Segment::Descendants => select_from(&child, segments, result)?,
+            Segment::Step {
+                id,
+                chunk_type,
+                index,
+            } => {
+                if !matches(id, child.id()?.as_bytes()) {
+                    continue;
+                }
+                if let Some(chunk_type) = chunk_type {
+                    if child.kind() != ChunkKind::List
+                        || !matches(chunk_type, child.chunk_type()?.as_bytes())
+                    {
+                        continue;
+                    }
+                }
+                count += 1;
+                if index.is_none_or(|index| index + 1 == count) {
+                    select_from(&child, rest, result)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
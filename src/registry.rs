@@ -0,0 +1,302 @@
+use crate::{Chunk, ChunkKind, FourCC};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// What is known about a chunk identifier, or about a list type for `LIST` chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkInfo {
+    pub description: Cow<'static, str>,
+    /// Whether the chunk is expected to hold data or other chunks.
+    pub kind: ChunkKind,
+    /// Whether the chunk may appear at most once among its siblings.
+    pub singleton: bool,
+}
+
+/// Known form types, chunk identifiers and list types, with a description of each.
+///
+/// Chunks are registered either for a given form type or for every form type. `LIST` chunks
+/// are registered by their list type, such as `INFO` or `hdrl`. `Registry::new` knows about
+/// the formats this crate handles, and applications can register their own chunks on top.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{registry::{ChunkInfo, Registry}, ChunkKind, FourCC};
+/// let mut registry = Registry::new();
+/// let wave = FourCC::from_array(*b"WAVE");
+/// assert_eq!(registry.form_type(wave), Some("Waveform audio"));
+/// assert!(registry.lookup(Some(wave), FourCC::from_array(*b"fmt ")).unwrap().singleton);
+///
+/// let info = ChunkInfo {
+///     description: "Vendor settings".into(),
+///     kind: ChunkKind::Raw,
+///     singleton: true,
+/// };
+/// registry.register(Some(wave), FourCC::from_array(*b"vndr"), info);
+/// let found = registry.lookup(Some(wave), FourCC::from_array(*b"vndr")).unwrap();
+/// assert_eq!(found.description, "Vendor settings");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registry {
+    form_types: BTreeMap<FourCC, Cow<'static, str>>,
+    chunks: BTreeMap<(Option<FourCC>, FourCC), ChunkInfo>,
+}
+
+type Entry = (&'static [u8; 4], ChunkKind, bool, &'static str);
+
+const FORM_TYPES: &[(&[u8; 4], &str)] = &[
+    (b"WAVE", "Waveform audio"),
+    (b"AVI ", "Audio video interleave"),
+    (b"ACON", "Animated cursor"),
+    (b"PAL ", "Palette"),
+    (b"RMID", "RIFF MIDI"),
+    (b"WEBP", "WebP image"),
+    (b"sfbk", "SoundFont 2 bank"),
+    (b"DLS ", "Downloadable sounds"),
+];
+
+/// Chunks found under any form type.
+const COMMON: &[Entry] = &[
+    (b"JUNK", ChunkKind::Raw, false, "Padding"),
+    (b"PAD ", ChunkKind::Raw, false, "Padding"),
+    (b"DISP", ChunkKind::Raw, false, "Display representation"),
+    (b"seqt", ChunkKind::Seqt, false, "Sequence"),
+    (b"INFO", ChunkKind::List, true, "Information"),
+    (b"INAM", ChunkKind::Raw, true, "Name"),
+    (b"IART", ChunkKind::Raw, false, "Artist"),
+    (b"ICMT", ChunkKind::Raw, false, "Comments"),
+    (b"ICOP", ChunkKind::Raw, false, "Copyright"),
+    (b"ICRD", ChunkKind::Raw, false, "Creation date"),
+    (b"IENG", ChunkKind::Raw, false, "Engineer"),
+    (b"IGNR", ChunkKind::Raw, false, "Genre"),
+    (b"IKEY", ChunkKind::Raw, false, "Keywords"),
+    (b"IPRD", ChunkKind::Raw, false, "Product"),
+    (b"ISBJ", ChunkKind::Raw, false, "Subject"),
+    (b"ISFT", ChunkKind::Raw, false, "Software"),
+    (b"ISRC", ChunkKind::Raw, false, "Source"),
+    (b"ITCH", ChunkKind::Raw, false, "Technician"),
+];
+
+const WAVE: &[Entry] = &[
+    (b"fmt ", ChunkKind::Raw, true, "Format"),
+    (b"fact", ChunkKind::Raw, true, "Sample count"),
+    (b"data", ChunkKind::Raw, true, "Samples"),
+    (b"cue ", ChunkKind::Raw, true, "Cue points"),
+    (b"plst", ChunkKind::Raw, true, "Playlist"),
+    (b"smpl", ChunkKind::Raw, true, "Sampler settings"),
+    (b"inst", ChunkKind::Raw, true, "Instrument settings"),
+    (b"bext", ChunkKind::Raw, true, "Broadcast extension"),
+    (b"adtl", ChunkKind::List, true, "Associated data"),
+    (b"labl", ChunkKind::Raw, false, "Cue label"),
+    (b"note", ChunkKind::Raw, false, "Cue note"),
+    (b"ltxt", ChunkKind::Raw, false, "Cue text"),
+];
+
+const AVI: &[Entry] = &[
+    (b"hdrl", ChunkKind::List, true, "Header"),
+    (b"avih", ChunkKind::Raw, true, "Main header"),
+    (b"strl", ChunkKind::List, false, "Stream"),
+    (b"strh", ChunkKind::Raw, true, "Stream header"),
+    (b"strf", ChunkKind::Raw, true, "Stream format"),
+    (b"strd", ChunkKind::Raw, true, "Stream codec data"),
+    (b"strn", ChunkKind::Raw, true, "Stream name"),
+    (b"indx", ChunkKind::Raw, true, "Stream index"),
+    (b"odml", ChunkKind::List, true, "OpenDML header"),
+    (b"dmlh", ChunkKind::Raw, true, "Extended header"),
+    (b"vedt", ChunkKind::Raw, true, "Video editing"),
+    (b"movi", ChunkKind::List, true, "Movie data"),
+    (b"rec ", ChunkKind::List, false, "Interleaved record"),
+    (b"idx1", ChunkKind::Raw, true, "Legacy index"),
+];
+
+const ACON: &[Entry] = &[
+    (b"anih", ChunkKind::Raw, true, "Animation header"),
+    (b"rate", ChunkKind::Raw, true, "Frame rates"),
+    (b"seq ", ChunkKind::Raw, true, "Frame sequence"),
+    (b"fram", ChunkKind::List, true, "Frames"),
+    (b"icon", ChunkKind::Raw, false, "Frame"),
+];
+
+const PAL: &[Entry] = &[(b"data", ChunkKind::Raw, true, "Palette entries")];
+
+const RMID: &[Entry] = &[(b"data", ChunkKind::Raw, true, "Standard MIDI file")];
+
+const WEBP: &[Entry] = &[
+    (b"VP8 ", ChunkKind::Raw, true, "Lossy bitstream"),
+    (b"VP8L", ChunkKind::Raw, true, "Lossless bitstream"),
+    (b"VP8X", ChunkKind::Raw, true, "Extended header"),
+    (b"ALPH", ChunkKind::Raw, true, "Alpha channel"),
+    (b"ANIM", ChunkKind::Raw, true, "Animation parameters"),
+    (b"ANMF", ChunkKind::Raw, false, "Animation frame"),
+    (b"ICCP", ChunkKind::Raw, true, "Color profile"),
+    (b"EXIF", ChunkKind::Raw, true, "EXIF metadata"),
+    (b"XMP ", ChunkKind::Raw, true, "XMP metadata"),
+];
+
+const SFBK: &[Entry] = &[
+    (b"ifil", ChunkKind::Raw, true, "Version"),
+    (b"isng", ChunkKind::Raw, true, "Target sound engine"),
+    (b"irom", ChunkKind::Raw, true, "Sound ROM name"),
+    (b"iver", ChunkKind::Raw, true, "Sound ROM version"),
+    (b"sdta", ChunkKind::List, true, "Sample data"),
+    (b"smpl", ChunkKind::Raw, true, "16-bit samples"),
+    (b"sm24", ChunkKind::Raw, true, "24-bit sample extension"),
+    (b"pdta", ChunkKind::List, true, "Preset data"),
+    (b"phdr", ChunkKind::Raw, true, "Preset headers"),
+    (b"pbag", ChunkKind::Raw, true, "Preset zones"),
+    (b"pmod", ChunkKind::Raw, true, "Preset modulators"),
+    (b"pgen", ChunkKind::Raw, true, "Preset generators"),
+    (b"inst", ChunkKind::Raw, true, "Instruments"),
+    (b"ibag", ChunkKind::Raw, true, "Instrument zones"),
+    (b"imod", ChunkKind::Raw, true, "Instrument modulators"),
+    (b"igen", ChunkKind::Raw, true, "Instrument generators"),
+    (b"shdr", ChunkKind::Raw, true, "Sample headers"),
+];
+
+const DLS: &[Entry] = &[
+    (b"colh", ChunkKind::Raw, true, "Instrument count"),
+    (b"vers", ChunkKind::Raw, true, "Version"),
+    (b"dlid", ChunkKind::Raw, true, "Unique identifier"),
+    (b"ptbl", ChunkKind::Raw, true, "Pool table"),
+    (b"lins", ChunkKind::List, true, "Instruments"),
+    (b"ins ", ChunkKind::List, false, "Instrument"),
+    (b"insh", ChunkKind::Raw, true, "Instrument header"),
+    (b"lrgn", ChunkKind::List, true, "Regions"),
+    (b"rgn ", ChunkKind::List, false, "Region"),
+    (b"rgn2", ChunkKind::List, false, "Level 2 region"),
+    (b"rgnh", ChunkKind::Raw, true, "Region header"),
+    (b"wlnk", ChunkKind::Raw, true, "Wave link"),
+    (b"wsmp", ChunkKind::Raw, true, "Wave sample"),
+    (b"lart", ChunkKind::List, false, "Articulators"),
+    (b"lar2", ChunkKind::List, false, "Level 2 articulators"),
+    (b"art1", ChunkKind::Raw, false, "Level 1 articulator"),
+    (b"art2", ChunkKind::Raw, false, "Level 2 articulator"),
+    (b"wvpl", ChunkKind::List, true, "Wave pool"),
+    (b"wave", ChunkKind::List, false, "Wave"),
+    (b"fmt ", ChunkKind::Raw, true, "Wave format"),
+    (b"data", ChunkKind::Raw, true, "Wave samples"),
+];
+
+impl Registry {
+    /// A registry knowing about the formats this crate handles.
+    pub fn new() -> Registry {
+        let mut registry = Registry::empty();
+        for (form_type, description) in FORM_TYPES {
+            registry.register_form_type(FourCC::new_unchecked(**form_type), *description);
+        }
+        let scoped = [
+            (None, COMMON),
+            (Some(b"WAVE"), WAVE),
+            (Some(b"AVI "), AVI),
+            (Some(b"ACON"), ACON),
+            (Some(b"PAL "), PAL),
+            (Some(b"RMID"), RMID),
+            (Some(b"WEBP"), WEBP),
+            (Some(b"sfbk"), SFBK),
+            (Some(b"DLS "), DLS),
+        ];
+        for (form_type, entries) in scoped.iter() {
+            let form_type = form_type.map(|form_type| FourCC::new_unchecked(*form_type));
+            for (id, kind, singleton, description) in entries.iter() {
+                let info = ChunkInfo {
+                    description: Cow::Borrowed(*description),
+                    kind: *kind,
+                    singleton: *singleton,
+                };
+                registry.register(form_type, FourCC::new_unchecked(**id), info);
+            }
+        }
+        registry
+    }
+
+    /// A registry knowing about nothing.
+    pub fn empty() -> Registry {
+        Registry {
+            form_types: BTreeMap::new(),
+            chunks: BTreeMap::new(),
+        }
+    }
+
+    /// Registers `form_type`, replacing its previous description.
+    pub fn register_form_type<D>(&mut self, form_type: FourCC, description: D) -> &mut Self
+    where
+        D: Into<Cow<'static, str>>,
+    {
+        self.form_types.insert(form_type, description.into());
+        self
+    }
+
+    /// Registers `id` for files of `form_type`, or of every form type if `None`, replacing any
+    /// previous registration. For `LIST` chunks, `id` is the list type.
+    pub fn register(
+        &mut self,
+        form_type: Option<FourCC>,
+        id: FourCC,
+        info: ChunkInfo,
+    ) -> &mut Self {
+        self.chunks.insert((form_type, id), info);
+        self
+    }
+
+    /// The description of `form_type`, if it is known.
+    pub fn form_type(&self, form_type: FourCC) -> Option<&str> {
+        self.form_types
+            .get(&form_type)
+            .map(|description| description.as_ref())
+    }
+
+    /// What is known about `id` in a file of `form_type`.
+    ///
+    /// Chunks registered for `form_type` come first, then chunks registered for every form type.
+    /// When `form_type` is `None` or unknown, chunks registered for any form type are considered
+    /// as a last resort.
+    pub fn lookup(&self, form_type: Option<FourCC>, id: FourCC) -> Option<&ChunkInfo> {
+        if let Some(info) = form_type.and_then(|form_type| self.chunks.get(&(Some(form_type), id)))
+        {
+            return Some(info);
+        }
+        if let Some(info) = self.chunks.get(&(None, id)) {
+            return Some(info);
+        }
+        if form_type.is_some_and(|form_type| self.form_types.contains_key(&form_type)) {
+            return None;
+        }
+        self.chunks
+            .iter()
+            .find(|((_, found), _)| *found == id)
+            .map(|(_, info)| info)
+    }
+
+    /// What is known about `chunk`, given the form type of the file holding it.
+    ///
+    /// `LIST` chunks are looked up by their list type. The root chunk is described by its form
+    /// type, through `Registry::form_type`, so this returns `None` for it.
+    pub fn lookup_chunk(&self, chunk: &Chunk<'_>) -> Option<&ChunkInfo> {
+        let mut root = chunk.parent()?;
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+        let form_type = root.chunk_type().ok();
+        let id = match chunk.kind() {
+            ChunkKind::List => chunk.chunk_type().ok()?,
+            _ => chunk.id().ok()?,
+        };
+        self.lookup(form_type, id)
+    }
+
+    /// The identifiers registered as containers, with the form type they are registered for.
+    pub(crate) fn containers(
+        &self,
+    ) -> impl Iterator<Item = (FourCC, Option<FourCC>, ChunkKind)> + '_ {
+        self.chunks
+            .iter()
+            .filter(|(_, info)| info.kind != ChunkKind::Raw)
+            .map(|((form_type, id), info)| (*id, *form_type, info.kind))
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
@@ -0,0 +1,443 @@
+use crate::{
+    constants::{LIST_ID, RIFF_ID, SEQT_ID_LOWERCASE, SEQT_ID_UPPERCASE},
+    error::{Location, RiffResult},
+    fourcc::is_printable,
+    riff::MAX_DEPTH,
+    writer::ChunkContents,
+    FourCC, RiffError,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+const AVI_ID: &[u8; 4] = b"AVI ";
+const MOVI_ID: &[u8; 4] = b"movi";
+const IDX1_ID: &[u8; 4] = b"idx1";
+const DATA_ID: &[u8; 4] = b"data";
+const JUNK_ID: &[u8; 4] = b"JUNK";
+
+/// Flags of an `idx1` entry pointing at a `LIST` and at a chunk that can be decoded on its own.
+const AVIIF_LIST: u32 = 0x01;
+const AVIIF_KEYFRAME: u32 = 0x10;
+const IDX1_ENTRY_LEN: usize = 16;
+
+/// A modification made by `repair`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The size field of the chunk was replaced by the size of its actual content.
+    SizeFixed { old: u32, new: u32 },
+    /// Bytes that did not form a complete chunk were dropped.
+    Truncated { len: usize },
+    /// The AVI `idx1` chunk was missing or stale and was rebuilt from the `movi` list.
+    IndexRebuilt { entries: usize },
+}
+
+/// A change along with the location of the affected chunk in the original file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub location: Location,
+    pub kind: ChangeKind,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ChangeKind::SizeFixed { old, new } => write!(f, "size {} fixed to {}", old, new)?,
+            ChangeKind::Truncated { len } => write!(f, "dropped {} bytes", len)?,
+            ChangeKind::IndexRebuilt { entries } => {
+                write!(f, "rebuilt index of {} entries", entries)?
+            }
+        }
+        write!(f, " at {}", self.location)
+    }
+}
+
+/// The repaired structure of a file, borrowing the payloads of the original, and the changes
+/// made to obtain it.
+#[derive(Debug, Clone)]
+pub struct Repaired<'a> {
+    pub contents: ChunkContents<'a>,
+    pub changes: Vec<Change>,
+}
+
+/// Rebuilds the structure of a damaged RIFF file held in `data`, such as one left behind by a
+/// recorder that crashed before updating its size fields.
+///
+/// The size fields are not trusted: the root spans the whole buffer, partial chunks at the end
+/// of a list are dropped, and the `idx1` of an AVI file is rebuilt if it does not match the
+/// `movi` list. `movi` lists are extended over the stream chunks that follow them, and `data`
+/// chunks are extended when their size is zero, too large, or too small to reach the next chunk
+/// in their list. Writing `contents` out recomputes every size.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::repair::{repair, ChangeKind};
+/// // A WAVE whose `RIFF` and `data` sizes were never updated.
+/// let bytes = b"RIFF\0\0\0\0WAVEdata\0\0\0\0abcd".to_vec();
+/// let repaired = repair(&bytes).unwrap();
+/// assert_eq!(repaired.changes.len(), 2);
+/// assert_eq!(repaired.contents.to_bytes().unwrap(), b"RIFF\x10\0\0\0WAVEdata\x04\0\0\0abcd");
+/// ```
+pub fn repair(data: &[u8]) -> RiffResult<Repaired<'_>> {
+    let header = data.get(0..12).ok_or_else(|| RiffError::TruncatedHeader {
+        location: Location::default(),
+        needed: 12,
+        available: data.len(),
+    })?;
+    if &header[0..4] != RIFF_ID {
+        return Err(RiffError::malformed("not a RIFF file"));
+    }
+    let form_type = FourCC::new(&header[8..12])?;
+    let path = format!("RIFF:{}", String::from_utf8_lossy(form_type.as_bytes()));
+    let mut changes = Vec::new();
+    let mut scanner = Scanner {
+        data,
+        changes: &mut changes,
+        depth: 0,
+    };
+    let mut children = scanner.sequence(12, data.len(), &path);
+    if form_type.as_bytes() == AVI_ID {
+        scanner.rebuild_index(&mut children, &path);
+    }
+    let contents = ChunkContents::Children(FourCC::new(RIFF_ID)?, form_type, children);
+    let old = u32_at(data, 4);
+    let new = contents.payload_len();
+    if u64::from(old) != new {
+        changes.insert(
+            0,
+            Change {
+                location: Location::new(0, path),
+                kind: ChangeKind::SizeFixed {
+                    old,
+                    new: u32::try_from(new)?,
+                },
+            },
+        );
+    }
+    Ok(Repaired { contents, changes })
+}
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+/// Whether `id` names a chunk of stream data inside `movi`, such as `00dc` or `01wb`.
+fn is_stream_chunk(id: &[u8]) -> bool {
+    id[0].is_ascii_digit() && id[1].is_ascii_digit()
+}
+
+struct Scanner<'a, 'c> {
+    data: &'a [u8],
+    changes: &'c mut Vec<Change>,
+    /// The depth of the list being scanned, the root being 0.
+    depth: usize,
+}
+
+impl<'a, 'c> Scanner<'a, 'c> {
+    fn record(&mut self, offset: usize, path: String, kind: ChangeKind) {
+        self.changes.push(Change {
+            location: Location::new(offset, path),
+            kind,
+        });
+    }
+
+    /// Scans the chunks laid out between `start` and `end`, dropping a partial chunk at the end.
+    fn sequence(&mut self, start: usize, end: usize, path: &str) -> Vec<ChunkContents<'a>> {
+        self.children(start, end, None, path).0
+    }
+
+    /// Scans the chunks of a list laid out between `start` and `end`, like `sequence`.
+    ///
+    /// If the children up to `fit` need no change, scanning stops there and `true` is returned,
+    /// so that a list whose declared size holds its children exactly keeps that size. Each list
+    /// is scanned once, however deeply it is nested.
+    fn children(
+        &mut self,
+        start: usize,
+        end: usize,
+        fit: Option<usize>,
+        path: &str,
+    ) -> (Vec<ChunkContents<'a>>, bool) {
+        let mark = self.changes.len();
+        let mut result = Vec::new();
+        let mut cursor = start;
+        if fit == Some(start) {
+            return (result, true);
+        }
+        while cursor < end {
+            let bound = fit.filter(|&fit| cursor < fit).unwrap_or(end);
+            match self.chunk(cursor, end, bound, path) {
+                Some((contents, next)) => {
+                    let unpadded = cursor + 8 + contents.payload_len() as usize;
+                    result.push(contents);
+                    cursor = next;
+                    if bound < end
+                        && unpadded <= bound
+                        && next >= bound
+                        && self.changes.len() == mark
+                    {
+                        return (result, true);
+                    }
+                }
+                None => {
+                    self.record(
+                        cursor,
+                        path.to_string(),
+                        ChangeKind::Truncated { len: end - cursor },
+                    );
+                    break;
+                }
+            }
+        }
+        (result, false)
+    }
+
+    /// Scans the chunk at `cursor`, returning it along with the position of the next chunk.
+    ///
+    /// `bound` is where the parent of the chunk is expected to end, at most `end`. A `movi` list
+    /// does not extend past it, and a `data` chunk is only extended when the bytes following it
+    /// before `bound` do not start another chunk. Lists whose children would be nested deeper
+    /// than `MAX_DEPTH` are kept as raw data.
+    fn chunk(
+        &mut self,
+        cursor: usize,
+        end: usize,
+        bound: usize,
+        parent_path: &str,
+    ) -> Option<(ChunkContents<'a>, usize)> {
+        let header = self
+            .data
+            .get(cursor..cursor + 8)
+            .filter(|_| cursor + 8 <= end)?;
+        let id = FourCC::new(&header[0..4]).ok()?;
+        let declared = u32_at(header, 4);
+        let available = end - cursor - 8;
+        let payload_start = cursor + 8;
+        let nests = self.depth + 1 < MAX_DEPTH;
+        let (contents, payload_len) = match &id.as_bytes()[..] {
+            LIST_ID | RIFF_ID if nests => {
+                let chunk_type = FourCC::new(self.data.get(payload_start..payload_start + 4)?)
+                    .ok()
+                    .filter(|_| available >= 4)?;
+                let path = format!(
+                    "{}/{}:{}",
+                    parent_path,
+                    String::from_utf8_lossy(id.as_bytes()),
+                    String::from_utf8_lossy(chunk_type.as_bytes())
+                );
+                self.depth += 1;
+                let (children, span) = if chunk_type.as_bytes() == MOVI_ID {
+                    let span = self.movi_span(payload_start, bound.max(payload_start + 4));
+                    let children = self.sequence(payload_start + 4, payload_start + span, &path);
+                    (children, span)
+                } else {
+                    self.list(payload_start, declared, available, 4, &path)
+                };
+                self.depth -= 1;
+                let contents = ChunkContents::Children(id, chunk_type, children);
+                let len = contents.payload_len() as usize;
+                self.fix_size(cursor, path, declared, len);
+                (contents, span)
+            }
+            SEQT_ID_LOWERCASE | SEQT_ID_UPPERCASE if nests => {
+                let path = format!("{}/{}", parent_path, String::from_utf8_lossy(id.as_bytes()));
+                self.depth += 1;
+                let (children, span) = self.list(payload_start, declared, available, 0, &path);
+                self.depth -= 1;
+                let contents = ChunkContents::ChildrenNoType(id, children);
+                let len = contents.payload_len() as usize;
+                self.fix_size(cursor, path, declared, len);
+                (contents, span)
+            }
+            _ => {
+                let path = format!("{}/{}", parent_path, String::from_utf8_lossy(id.as_bytes()));
+                let is_data = id.as_bytes() == DATA_ID;
+                let len = if declared as usize <= available
+                    && !(is_data
+                        && (declared == 0 || self.is_stale(payload_start, declared, bound)))
+                {
+                    declared as usize
+                } else if is_data {
+                    self.fix_size(cursor, path, declared, available);
+                    available
+                } else {
+                    return None;
+                };
+                let payload = &self.data[payload_start..payload_start + len];
+                (ChunkContents::RawData(id, Cow::Borrowed(payload)), len)
+            }
+        };
+        let next = (payload_start + payload_len + payload_len % 2).min(end);
+        Some((contents, next))
+    }
+
+    /// Scans the list whose payload starts at `payload_start` and whose children start `min`
+    /// bytes in, returning its children and the number of bytes of payload it spans.
+    ///
+    /// The declared size is kept if the children fit in it exactly, otherwise the list extends
+    /// over everything `available`.
+    fn list(
+        &mut self,
+        payload_start: usize,
+        declared: u32,
+        available: usize,
+        min: usize,
+        path: &str,
+    ) -> (Vec<ChunkContents<'a>>, usize) {
+        let declared = declared as usize;
+        let fit =
+            Some(payload_start + declared).filter(|_| declared >= min && declared <= available);
+        let end = payload_start + available;
+        match self.children(payload_start + min, end, fit, path) {
+            (children, true) => (children, declared),
+            (children, false) => (children, available),
+        }
+    }
+
+    /// The number of bytes from `payload_start` that belong to a `movi` list, which cannot
+    /// extend past `end`.
+    fn movi_span(&self, payload_start: usize, end: usize) -> usize {
+        // Recorders append to `movi` until they stop, so it extends over every stream chunk that
+        // follows, whatever its size says.
+        let data = &self.data[..end];
+        let available = end - payload_start;
+        let mut span = 4;
+        while span < available {
+            let header = match data.get(payload_start + span..payload_start + span + 8) {
+                Some(header) => header,
+                None => break,
+            };
+            let id = &header[0..4];
+            if !(is_stream_chunk(id) || id == LIST_ID || id == JUNK_ID) {
+                break;
+            }
+            let len = u32_at(header, 4) as usize;
+            span = (span + 8).saturating_add(len + len % 2).min(available);
+        }
+        span
+    }
+
+    /// Whether the `data` chunk whose payload starts at `payload_start` is followed, before
+    /// `bound`, by bytes that cannot start a chunk, as when its size was not updated after the
+    /// samples written last.
+    fn is_stale(&self, payload_start: usize, declared: u32, bound: usize) -> bool {
+        let declared = declared as usize;
+        let next = payload_start + declared + declared % 2;
+        next < bound
+            && !self
+                .data
+                .get(next..next + 4)
+                .filter(|_| next + 4 <= bound)
+                .is_some_and(is_printable)
+    }
+
+    fn fix_size(&mut self, cursor: usize, path: String, old: u32, new: usize) {
+        if old as usize != new {
+            let new = u32::try_from(new).unwrap_or(u32::MAX);
+            self.record(cursor, path, ChangeKind::SizeFixed { old, new });
+        }
+    }
+
+    /// Replaces the `idx1` chunk among `children` unless it matches the `movi` list.
+    fn rebuild_index(&mut self, children: &mut Vec<ChunkContents<'a>>, path: &str) {
+        let movi_index = match children.iter().position(is_movi) {
+            Some(index) => index,
+            None => return,
+        };
+        // Offset of the `movi` form type in the repaired file.
+        let movi_offset = 12
+            + children[..movi_index]
+                .iter()
+                .map(|c| c.total_len())
+                .sum::<u64>()
+            + 8;
+        let mut expected = Vec::new();
+        if let ChunkContents::Children(_, _, movi_children) = &children[movi_index] {
+            index_entries(movi_children, 4, &mut expected);
+        }
+        let idx1_index = children
+            .iter()
+            .position(|c| matches!(c, ChunkContents::RawData(id, _) if id.as_bytes() == IDX1_ID));
+        if let Some(ChunkContents::RawData(_, existing)) = idx1_index.map(|i| &children[i]) {
+            if index_matches(existing, &expected, movi_offset) {
+                return;
+            }
+        }
+        let mut index = Vec::with_capacity(expected.len() * IDX1_ENTRY_LEN);
+        for (ckid, flags, offset, size) in &expected {
+            index.extend_from_slice(ckid);
+            index.extend_from_slice(&flags.to_le_bytes());
+            index.extend_from_slice(&(*offset as u32).to_le_bytes());
+            index.extend_from_slice(&size.to_le_bytes());
+        }
+        let contents = ChunkContents::RawData(FourCC::from_array(*IDX1_ID), Cow::Owned(index));
+        let offset = match idx1_index {
+            Some(i) => {
+                children[i] = contents;
+                12 + children[..i].iter().map(|c| c.total_len()).sum::<u64>() as usize
+            }
+            None => {
+                children.insert(movi_index + 1, contents);
+                self.data.len()
+            }
+        };
+        self.record(
+            offset,
+            format!("{}/idx1", path),
+            ChangeKind::IndexRebuilt {
+                entries: expected.len(),
+            },
+        );
+    }
+}
+
+fn is_movi(contents: &ChunkContents<'_>) -> bool {
+    matches!(contents, ChunkContents::Children(id, chunk_type, _)
+        if &id.as_bytes()[..] == LIST_ID && chunk_type.as_bytes() == MOVI_ID)
+}
+
+/// Collects the `idx1` entries of `children`, the first of which is at `offset` relative to
+/// the `movi` form type, as `(ckid, flags, offset, size)`.
+fn index_entries(
+    children: &[ChunkContents<'_>],
+    mut offset: u64,
+    entries: &mut Vec<([u8; 4], u32, u64, u32)>,
+) {
+    for child in children {
+        let size = child.payload_len() as u32;
+        match child {
+            ChunkContents::Children(_, chunk_type, grandchildren) => {
+                entries.push((*chunk_type.as_bytes(), AVIIF_LIST, offset, size));
+                index_entries(grandchildren, offset + 12, entries);
+            }
+            ChunkContents::RawData(id, _) if id.as_bytes() != JUNK_ID => {
+                entries.push((*id.as_bytes(), AVIIF_KEYFRAME, offset, size));
+            }
+            _ => {}
+        }
+        offset += child.total_len();
+    }
+}
+
+/// Whether every entry of `existing` points at a chunk listed in `expected`, with offsets
+/// relative either to the `movi` form type or to the start of the file.
+fn index_matches(existing: &[u8], expected: &[([u8; 4], u32, u64, u32)], movi_offset: u64) -> bool {
+    if existing.is_empty() || !existing.len().is_multiple_of(IDX1_ENTRY_LEN) {
+        return false;
+    }
+    let chunks: HashMap<u64, (&[u8; 4], u32)> = expected
+        .iter()
+        .map(|(ckid, _, offset, size)| (*offset, (ckid, *size)))
+        .collect();
+    [0, movi_offset].iter().any(|base| {
+        existing.chunks(IDX1_ENTRY_LEN).all(|entry| {
+            let offset = u64::from(u32_at(entry, 8));
+            offset >= *base
+                && chunks
+                    .get(&(offset - base))
+                    .is_some_and(|(ckid, size)| &entry[0..4] == *ckid && u32_at(entry, 12) == *size)
+        })
+    })
+}
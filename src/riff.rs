@@ -1,68 +1,303 @@
 use crate::{
+    codec::ChunkCodec,
     constants::{LIST_ID, RIFF_ID, SEQT_ID_LOWERCASE, SEQT_ID_UPPERCASE},
-    error::RiffResult,
+    edit::Editor,
+    error::{Location, RiffResult},
+    fourcc::is_printable,
+    options::{ParseContext, ParseOptions, Warning, WarningKind},
+    query::Query,
+    registry::Registry,
+    repair::{self, Change},
+    validate::{self, Report},
+    walk::{self, Visitor, Walk},
     FourCC, RiffError,
 };
 use memmap::Mmap;
-use std::convert::TryInto;
+use std::convert::TryFrom;
+use std::io::Write;
 use std::path::Path;
+use std::sync::{Arc, OnceLock};
 use std::{fmt::Debug, fs::File};
 
 #[derive(Debug)]
 pub struct Riff {
     inner: Mmap,
+    options: ParseOptions,
 }
 
 impl Riff {
     pub fn from_path<P>(path: P) -> RiffResult<Riff>
+    where
+        P: AsRef<Path>,
+    {
+        Riff::from_path_with_options(path, ParseOptions::default())
+    }
+
+    pub fn from_path_with_options<P>(path: P, options: ParseOptions) -> RiffResult<Riff>
     where
         P: AsRef<Path>,
     {
         let inner = unsafe { Mmap::map(&File::open(&path)?)? };
-        Ok(Riff { inner })
+        Ok(Riff { inner, options })
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.inner
+    }
+
+    /// Parses the root chunk of the file.
+    ///
+    /// Bytes following the root chunk are recorded as a `TrailingBytes` warning, unless the
+    /// options say to reject them.
+    pub fn as_chunk(&self) -> RiffResult<Chunk<'_>> {
+        let chunk = Chunk::from_bytes_with_options(&self.inner, self.options.clone())?;
+        let trailing = self.inner.len() - chunk.data.len();
+        if trailing > 0 {
+            let location = Location::new(chunk.data.len(), String::new());
+            if self.options.rejects_trailing() {
+                return Err(RiffError::MalformedChunk {
+                    location,
+                    reason: "trailing bytes after the root chunk",
+                });
+            }
+            chunk
+                .context
+                .warn(location, WarningKind::TrailingBytes { len: trailing });
+        }
+        Ok(chunk)
+    }
+
+    /// Starts recording structural edits, to be written out as a new file.
+    pub fn edit(&self) -> RiffResult<Editor<'_>> {
+        Ok(Editor::new(self.as_chunk()?))
+    }
+
+    /// Writes a repaired copy of the file to `writer` and returns the changes made.
+    pub fn repair_to<W>(&self, writer: &mut W) -> RiffResult<Vec<Change>>
+    where
+        W: Write,
+    {
+        let repaired = repair::repair(&self.inner)?;
+        repaired.contents.write(writer)?;
+        Ok(repaired.changes)
     }
 
-    pub fn as_chunk(&self) -> RiffResult<Chunk> {
-        Chunk::from_bytes(&self.inner)
+    /// Checks the structure of the whole file, reading the containers registered in the options
+    /// it was opened with.
+    pub fn validate(&self) -> Report {
+        validate::validate_with_options(&self.inner, &Registry::new(), self.options.clone())
     }
 }
 
-/// Represents the possible data contained in a `ChunkDisk`.
-#[derive(Debug)]
-pub enum Chunk<'a> {
-    Raw(&'a [u8]),
-    List(&'a [u8]),
-    Seqt(&'a [u8]),
+/// The deepest a chunk can be nested, counting the chunks holding it.
+///
+/// Real files nest a handful of levels at most. Parsing a chunk nested deeper fails, so that
+/// crafted files cannot exhaust the stack of the code walking them.
+pub const MAX_DEPTH: usize = 64;
+
+/// Represents the possible data contained in a `Chunk`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkKind {
+    Raw,
+    /// A `RIFF` or `LIST` chunk, holding a form type followed by chunks.
+    List,
+    /// A `seqt` chunk, holding chunks without a form type.
+    Seqt,
+}
+
+/// A view of a chunk inside a buffer.
+///
+/// Besides its own bytes, a chunk remembers its absolute offset within the buffer it was
+/// parsed from and the chain of chunks containing it, so errors can say where they happened.
+#[derive(Debug, Clone)]
+pub struct Chunk<'a> {
+    kind: ChunkKind,
+    data: &'a [u8],
+    /// The size of the payload, which is smaller than the size field if it was clamped.
+    payload_len: u32,
+    offset: usize,
+    parent: Option<Arc<Chunk<'a>>>,
+    /// Absolute offset of the first chunk of the sequence this chunk was iterated from.
+    first_sibling: usize,
+    /// The number of chunks holding this one.
+    depth: usize,
+    context: Arc<ParseContext>,
+    /// The path of the chunk, computed the first time it is needed.
+    path: OnceLock<String>,
+}
+
+/// The parts of a chunk that do not borrow its buffer, from which the chunk can be rebuilt.
+#[derive(Debug, Clone)]
+pub(crate) struct Frame {
+    kind: ChunkKind,
+    offset: usize,
+    len: usize,
+    payload_len: u32,
+    first_sibling: usize,
+}
+
+/// Renders the path segment of a chunk, including the form type of a list if known.
+fn path_segment(id: &[u8], chunk_type: Option<&[u8]>) -> String {
+    let mut result = String::from_utf8_lossy(id).into_owned();
+    if let Some(chunk_type) = chunk_type {
+        result.push(':');
+        result.push_str(&String::from_utf8_lossy(chunk_type));
+    }
+    result
+}
+
+/// Whether `data` cannot hold a chunk: it is too short for a header, or it has a non-printable
+/// identifier along with a size that does not fit.
+fn is_junk(data: &[u8]) -> bool {
+    match data.get(0..8) {
+        None => true,
+        Some(header) => {
+            let payload_len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+            !is_printable(&header[0..4]) && payload_len as usize > data.len() - 8
+        }
+    }
 }
 
 impl<'a> Chunk<'a> {
-    pub fn from_bytes(data: &[u8]) -> RiffResult<Chunk> {
-        let id = data.get(0..4).ok_or(RiffError::InsufficientBytes)?;
-        let payload_len = u32::from_le_bytes(
-            data.get(4..8)
-                .ok_or(RiffError::InsufficientBytes)?
-                .try_into()?,
-        ) as usize;
-        let data = data
-            .get(0..payload_len + payload_len % 2 + 8)
-            .ok_or(RiffError::InsufficientBytes)?;
-        match id {
-            LIST_ID | RIFF_ID => Ok(Chunk::List(data)),
-            SEQT_ID_LOWERCASE | SEQT_ID_UPPERCASE => Ok(Chunk::Seqt(data)),
-            _ => Ok(Chunk::Raw(data)),
+    pub fn from_bytes(data: &'a [u8]) -> RiffResult<Chunk<'a>> {
+        Chunk::from_bytes_with_options(data, ParseOptions::default())
+    }
+
+    pub fn from_bytes_with_options(data: &'a [u8], options: ParseOptions) -> RiffResult<Chunk<'a>> {
+        Chunk::parse(data, 0, None, Arc::new(ParseContext::new(options)))
+    }
+
+    /// Parses the chunk at the start of `data`, which lives at `offset` in the whole buffer.
+    fn parse(
+        data: &'a [u8],
+        offset: usize,
+        parent: Option<Arc<Chunk<'a>>>,
+        context: Arc<ParseContext>,
+    ) -> RiffResult<Chunk<'a>> {
+        let location = |segment: Option<String>| {
+            let mut path = parent.as_ref().map(|p| p.path()).unwrap_or_default();
+            if let Some(segment) = segment {
+                if !path.is_empty() {
+                    path.push('/');
+                }
+                path.push_str(&segment);
+            }
+            Location::new(offset, path)
+        };
+        let header = data.get(0..8).ok_or_else(|| RiffError::TruncatedHeader {
+            location: location(None),
+            needed: 8,
+            available: data.len(),
+        })?;
+        let id = &header[0..4];
+        let mut payload_len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let kind = match id {
+            LIST_ID | RIFF_ID => ChunkKind::List,
+            SEQT_ID_LOWERCASE | SEQT_ID_UPPERCASE => ChunkKind::Seqt,
+            _ => {
+                let form_type = parent.as_deref().and_then(Chunk::form_type);
+                context.options.container_kind(id, form_type)
+            }
+        };
+        let chunk_type = match kind {
+            ChunkKind::List => data.get(8..12),
+            _ => None,
+        };
+        let here = || location(Some(path_segment(id, chunk_type)));
+        let depth = parent.as_ref().map_or(0, |parent| parent.depth + 1);
+        if depth > MAX_DEPTH {
+            return Err(RiffError::MalformedChunk {
+                location: here(),
+                reason: "chunk nested too deeply",
+            });
+        }
+        let chunk_len = (payload_len as usize)
+            .checked_add(8 + payload_len as usize % 2)
+            .ok_or_else(|| RiffError::SizeOverflow { location: here() })?;
+        let data = match data.get(0..chunk_len) {
+            Some(data) => data,
+            None if !context.options.is_lenient() => {
+                return Err(RiffError::PayloadOverrunsParent {
+                    location: here(),
+                    payload_len,
+                    available: data.len() - 8,
+                })
+            }
+            None if payload_len % 2 == 1 && data.len() == chunk_len - 1 => {
+                context.warn(here(), WarningKind::MissingPadding);
+                data
+            }
+            None => {
+                let available = u32::try_from(data.len() - 8)?;
+                context.warn(
+                    here(),
+                    WarningKind::SizeClamped {
+                        declared: payload_len,
+                        available,
+                    },
+                );
+                payload_len = available;
+                data
+            }
+        };
+        if kind == ChunkKind::List && payload_len < 4 {
+            return Err(RiffError::TruncatedHeader {
+                location: here(),
+                needed: 12,
+                available: 8 + payload_len as usize,
+            });
+        }
+        Ok(Chunk {
+            kind,
+            data,
+            payload_len,
+            offset,
+            parent,
+            first_sibling: offset,
+            depth,
+            context,
+            path: OnceLock::new(),
+        })
+    }
+
+    pub fn kind(&self) -> ChunkKind {
+        self.kind
+    }
+
+    /// The form type of the root chunk this chunk belongs to, if the root is a list.
+    fn form_type(&self) -> Option<FourCC> {
+        let mut root = self;
+        while let Some(parent) = root.parent.as_deref() {
+            root = parent;
+        }
+        match root.kind {
+            ChunkKind::List => root.chunk_type().ok(),
+            _ => None,
         }
     }
 
     pub fn id(&self) -> RiffResult<FourCC> {
-        let id = &self.as_bytes()[0..4];
-        let result = FourCC::new(id)?;
-        Ok(result)
+        FourCC::new(&self.data[0..4])
     }
 
+    /// The size of the payload, excluding the header and pad byte.
+    ///
+    /// In lenient mode, this is smaller than the size field if the chunk was clamped.
     pub fn payload_len(&self) -> RiffResult<u32> {
-        let id = &self.as_bytes()[4..8].try_into().unwrap();
-        let result = u32::from_le_bytes(*id);
-        Ok(result)
+        Ok(self.payload_len)
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.context.options
+    }
+
+    /// The recoveries made so far while parsing the buffer this chunk belongs to.
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.context.warnings()
     }
 
     pub fn chunk_type(&self) -> RiffResult<FourCC> {
@@ -71,66 +306,311 @@ impl<'a> Chunk<'a> {
         Ok(result)
     }
 
-    fn read_n_bytes_from_offset(&self, offset: u32, count: u32) -> RiffResult<&[u8]> {
+    fn read_n_bytes_from_offset(&self, offset: u32, count: u32) -> RiffResult<&'a [u8]> {
         let pos_begin = offset as usize;
-        let pos_end = (offset + count) as usize;
-        let data = self
-            .as_bytes()
-            .get(pos_begin..pos_end)
-            .ok_or(RiffError::InsufficientBytes)?;
+        let pos_end =
+            pos_begin
+                .checked_add(count as usize)
+                .ok_or_else(|| RiffError::SizeOverflow {
+                    location: self.location(),
+                })?;
+        let data =
+            self.as_bytes()
+                .get(pos_begin..pos_end)
+                .ok_or_else(|| RiffError::TruncatedHeader {
+                    location: self.location(),
+                    needed: pos_end,
+                    available: self.as_bytes().len(),
+                })?;
         Ok(data)
     }
 
-    pub fn content(&self) -> RiffResult<&[u8]> {
+    /// The payload of the chunk, excluding the form type of a list.
+    pub fn content(&self) -> RiffResult<&'a [u8]> {
         let offset = self.content_offset();
-        let len = self.payload_len()?;
+        let len = (self.payload_len()?)
+            .checked_add(8)
+            .and_then(|len| len.checked_sub(offset))
+            .ok_or_else(|| RiffError::SizeOverflow {
+                location: self.location(),
+            })?;
         self.read_n_bytes_from_offset(offset, len)
     }
 
     pub fn content_offset(&self) -> u32 {
-        match self {
-            Chunk::Raw(_) => 8,
-            Chunk::List(_) => 12,
-            Chunk::Seqt(_) => 8,
+        match self.kind {
+            ChunkKind::Raw => 8,
+            ChunkKind::List => 12,
+            ChunkKind::Seqt => 8,
         }
     }
 
-    pub fn as_bytes(&self) -> &[u8] {
-        match self {
-            Chunk::Raw(data) => (data),
-            Chunk::List(data) => (data),
-            Chunk::Seqt(data) => (data),
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The absolute offset of this chunk within the buffer it was parsed from, such as the
+    /// memory map of a `Riff`.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The chunk holding this one, or `None` for the chunk a parse started from.
+    pub fn parent(&self) -> Option<Chunk<'a>> {
+        self.parent.as_deref().cloned()
+    }
+
+    /// The other chunks held by the parent of this chunk, in order.
+    ///
+    /// A chunk without a parent has no siblings. This includes the chunks yielded by
+    /// `ChunkIter::from_bytes`, since the sequence they were read from is not kept: collect the
+    /// iterator to get all of them.
+    pub fn siblings(&self) -> RiffResult<Vec<Chunk<'a>>> {
+        let parent = match &self.parent {
+            Some(parent) => parent,
+            None => return Ok(Vec::new()),
+        };
+        ChunkIter::new(parent, self.first_sibling - parent.offset)
+            .filter(|sibling| {
+                sibling
+                    .as_ref()
+                    .map_or(true, |sibling| sibling.offset != self.offset)
+            })
+            .collect()
+    }
+
+    /// The path from the root to this chunk, such as `RIFF:AVI /LIST:hdrl/avih`.
+    pub fn path(&self) -> String {
+        self.cached_path().to_string()
+    }
+
+    /// The path of this chunk, computed once and shared by everything holding the same parent.
+    fn cached_path(&self) -> &str {
+        if let Some(path) = self.path.get() {
+            return path;
+        }
+        // Fill in the ancestors from the top, so that a deep chain does not recurse.
+        let mut uncached = Vec::new();
+        let mut ancestor = self.parent.as_deref();
+        while let Some(chunk) = ancestor.filter(|chunk| chunk.path.get().is_none()) {
+            uncached.push(chunk);
+            ancestor = chunk.parent.as_deref();
+        }
+        for chunk in uncached.into_iter().rev() {
+            chunk.path.get_or_init(|| chunk.join_path());
         }
+        self.path.get_or_init(|| self.join_path())
     }
 
-    pub fn iter(&self) -> RiffResult<ChunkIter> {
-        let offset = self.content_offset();
-        let cursor_end = self.as_bytes().len() as u32;
-        match self {
-            Chunk::Raw(_) => Ok(ChunkIter {
+    /// Appends the segment of this chunk to the path of its parent, which must be computed.
+    fn join_path(&self) -> String {
+        let id = &self.data[0..4];
+        let chunk_type = match self.kind {
+            ChunkKind::List => self.data.get(8..12),
+            _ => None,
+        };
+        let segment = path_segment(id, chunk_type);
+        match self.parent.as_deref().map(Chunk::cached_path) {
+            Some(parent) => format!("{}/{}", parent, segment),
+            None => segment,
+        }
+    }
+
+    pub(crate) fn location(&self) -> Location {
+        Location::new(self.offset, self.path())
+    }
+
+    /// Whether this chunk is a `RIFF` or `LIST` with the form type `chunk_type`.
+    pub(crate) fn is_list_of(&self, chunk_type: &[u8; 4]) -> RiffResult<bool> {
+        Ok(self.kind == ChunkKind::List && self.chunk_type()?.as_bytes() == chunk_type)
+    }
+
+    /// Fails with `UnexpectedContainerType` unless this chunk has the form type `expected`.
+    pub(crate) fn expect_form_type(&self, expected: &[u8; 4]) -> RiffResult<()> {
+        let found = self.chunk_type()?;
+        if found.as_bytes() != expected {
+            return Err(RiffError::UnexpectedContainerType {
+                location: self.location(),
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Attaches the location of this chunk to an error raised while decoding its payload.
+    pub(crate) fn locate(&self, err: RiffError) -> RiffError {
+        err.or_location(|| self.location())
+    }
+
+    /// Decodes the payload as a `T`, checking that the identifier of the chunk is `T::ID`.
+    pub fn decode<T>(&self) -> RiffResult<T>
+    where
+        T: ChunkCodec<'a>,
+    {
+        if self.id()? != T::ID {
+            return Err(RiffError::MalformedChunk {
+                location: self.location(),
+                reason: "chunk identifier does not match the decoded type",
+            });
+        }
+        T::decode(self.content()?).map_err(|err| self.locate(err))
+    }
+
+    /// The chunks below this one matching `query`, such as `LIST:hdrl/LIST:strl[1]/strf`.
+    ///
+    /// See `Query` for the syntax.
+    pub fn select(&self, query: &str) -> RiffResult<Vec<Chunk<'a>>> {
+        Query::parse(query)?.select(self)
+    }
+
+    /// The first chunk below this one matching `query`.
+    pub fn select_first(&self, query: &str) -> RiffResult<Option<Chunk<'a>>> {
+        Ok(self.select(query)?.into_iter().next())
+    }
+
+    /// Walks this chunk and everything below it, depth first.
+    pub fn walk(&self) -> Walk<'a> {
+        Walk::new(self.clone())
+    }
+
+    /// Walks this chunk and everything below it, depth first, calling back `visitor`.
+    pub fn visit<V>(&self, visitor: &mut V) -> RiffResult<()>
+    where
+        V: Visitor<'a> + ?Sized,
+    {
+        walk::visit(self.clone(), 0, visitor)?;
+        Ok(())
+    }
+
+    /// The frames of the chunks from the root down to this one, if they all lie in `data`.
+    pub(crate) fn frames_within(&self, data: &[u8]) -> Option<Vec<Frame>> {
+        let mut frames = Vec::new();
+        let mut chunk = Some(self);
+        while let Some(current) = chunk {
+            let end = current.offset.checked_add(current.data.len())?;
+            if data.get(current.offset..end)?.as_ptr() != current.data.as_ptr() {
+                return None;
+            }
+            frames.push(Frame {
+                kind: current.kind,
+                offset: current.offset,
+                len: current.data.len(),
+                payload_len: current.payload_len,
+                first_sibling: current.first_sibling,
+            });
+            chunk = current.parent.as_deref();
+        }
+        frames.reverse();
+        Some(frames)
+    }
+
+    /// Rebuilds the chunk described by `frame` below `ancestors`, which were taken from `data`.
+    pub(crate) fn from_frames(
+        data: &'a [u8],
+        ancestors: &[Frame],
+        frame: &Frame,
+        context: &Arc<ParseContext>,
+    ) -> Chunk<'a> {
+        let parent = ancestors.iter().fold(None, |parent, ancestor| {
+            Some(Arc::new(Chunk::from_frame(data, ancestor, parent, context)))
+        });
+        Chunk::from_frame(data, frame, parent, context)
+    }
+
+    fn from_frame(
+        data: &'a [u8],
+        frame: &Frame,
+        parent: Option<Arc<Chunk<'a>>>,
+        context: &Arc<ParseContext>,
+    ) -> Chunk<'a> {
+        Chunk {
+            kind: frame.kind,
+            data: &data[frame.offset..frame.offset + frame.len],
+            payload_len: frame.payload_len,
+            offset: frame.offset,
+            depth: parent.as_ref().map_or(0, |parent| parent.depth + 1),
+            parent,
+            first_sibling: frame.first_sibling,
+            context: context.clone(),
+            path: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn context(&self) -> &Arc<ParseContext> {
+        &self.context
+    }
+
+    pub fn iter(&self) -> RiffResult<ChunkIter<'a>> {
+        match self.kind {
+            ChunkKind::Raw => Ok(ChunkIter {
                 cursor: 0,
-                cursor_end,
-                data: self.as_bytes(),
-                error_occurred: false,
-            }),
-            _ => Ok(ChunkIter {
-                cursor: offset,
-                cursor_end,
-                data: self.as_bytes(),
+                start: 0,
+                cursor_end: self.data.len(),
+                data: self.data,
+                base_offset: self.offset,
+                parent: self.parent.clone(),
+                context: self.context.clone(),
                 error_occurred: false,
             }),
+            _ => Ok(ChunkIter::new(self, self.content_offset() as usize)),
         }
     }
 }
 
 #[derive(Debug)]
 pub struct ChunkIter<'a> {
-    cursor: u32,
-    cursor_end: u32,
+    cursor: usize,
+    /// Where `cursor` started.
+    start: usize,
+    cursor_end: usize,
     data: &'a [u8],
+    /// Absolute offset of `data` within the whole buffer.
+    base_offset: usize,
+    parent: Option<Arc<Chunk<'a>>>,
+    context: Arc<ParseContext>,
     error_occurred: bool,
 }
 
+impl<'a> ChunkIter<'a> {
+    /// Iterates over chunks laid out back to back in `data`, which are not wrapped in a `LIST`.
+    pub fn from_bytes(data: &'a [u8]) -> ChunkIter<'a> {
+        ChunkIter::from_bytes_with_options(data, ParseOptions::default())
+    }
+
+    pub fn from_bytes_with_options(data: &'a [u8], options: ParseOptions) -> ChunkIter<'a> {
+        ChunkIter {
+            cursor: 0,
+            start: 0,
+            cursor_end: data.len(),
+            data,
+            base_offset: 0,
+            parent: None,
+            context: Arc::new(ParseContext::new(options)),
+            error_occurred: false,
+        }
+    }
+
+    /// The recoveries made so far while parsing the buffer this iterator walks.
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.context.warnings()
+    }
+
+    /// Iterates over the children of `parent` starting `cursor` bytes into it, such as the
+    /// frame data following the header of a WebP `ANMF` chunk.
+    pub(crate) fn new(parent: &Chunk<'a>, cursor: usize) -> ChunkIter<'a> {
+        ChunkIter {
+            cursor,
+            start: cursor,
+            cursor_end: 8 + parent.payload_len as usize,
+            data: parent.data,
+            base_offset: parent.offset,
+            parent: Some(Arc::new(parent.clone())),
+            context: parent.context.clone(),
+            error_occurred: false,
+        }
+    }
+}
+
 macro_rules! try_result {
     ( $self : ident , $value :expr ) => {
         match $value {
@@ -143,15 +623,6 @@ macro_rules! try_result {
     };
 }
 
-macro_rules! try_option {
-    ( $self : ident , $value :expr ) => {
-        match $value {
-            Some(r) => r,
-            None => return Some(Err(RiffError::InsufficientBytes)),
-        }
-    };
-}
-
 impl<'a> Iterator for ChunkIter<'a> {
     type Item = RiffResult<Chunk<'a>>;
 
@@ -159,12 +630,23 @@ impl<'a> Iterator for ChunkIter<'a> {
         if self.error_occurred || self.cursor >= self.cursor_end {
             None
         } else {
-            let cursor = self.cursor as usize;
-            let cursor_end = self.cursor_end as usize;
-            let data = try_option!(self, self.data.get(cursor..cursor_end));
-            let chunk = try_result!(self, Chunk::from_bytes(data));
-            let payload_len = try_result!(self, chunk.payload_len());
-            self.cursor += 8 + payload_len + payload_len % 2;
+            let data = &self.data[self.cursor..self.cursor_end];
+            let offset = self.base_offset + self.cursor;
+            if self.context.options.is_lenient() && is_junk(data) {
+                let path = self.parent.as_ref().map(|p| p.path()).unwrap_or_default();
+                self.context.warn(
+                    Location::new(offset, path),
+                    WarningKind::TrailingBytes { len: data.len() },
+                );
+                self.cursor = self.cursor_end;
+                return None;
+            }
+            let mut chunk = try_result!(
+                self,
+                Chunk::parse(data, offset, self.parent.clone(), self.context.clone())
+            );
+            chunk.first_sibling = self.base_offset + self.start;
+            self.cursor += chunk.data.len();
             Some(Ok(chunk))
         }
     }
@@ -0,0 +1,728 @@
+use crate::{error::RiffResult, writer::ChunkContents, Chunk, ChunkKind, FourCC, RiffError};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::ops::Range;
+
+pub const SFBK_ID: &[u8; 4] = b"sfbk";
+pub const INFO_ID: &[u8; 4] = b"INFO";
+pub const SDTA_ID: &[u8; 4] = b"sdta";
+pub const PDTA_ID: &[u8; 4] = b"pdta";
+
+pub const IFIL_ID: &[u8; 4] = b"ifil";
+pub const ISNG_ID: &[u8; 4] = b"isng";
+pub const INAM_ID: &[u8; 4] = b"INAM";
+pub const IROM_ID: &[u8; 4] = b"irom";
+pub const IVER_ID: &[u8; 4] = b"iver";
+pub const ICRD_ID: &[u8; 4] = b"ICRD";
+pub const IENG_ID: &[u8; 4] = b"IENG";
+pub const IPRD_ID: &[u8; 4] = b"IPRD";
+pub const ICOP_ID: &[u8; 4] = b"ICOP";
+pub const ICMT_ID: &[u8; 4] = b"ICMT";
+pub const ISFT_ID: &[u8; 4] = b"ISFT";
+
+pub const SMPL_ID: &[u8; 4] = b"smpl";
+pub const SM24_ID: &[u8; 4] = b"sm24";
+
+pub const PHDR_ID: &[u8; 4] = b"phdr";
+pub const PBAG_ID: &[u8; 4] = b"pbag";
+pub const PMOD_ID: &[u8; 4] = b"pmod";
+pub const PGEN_ID: &[u8; 4] = b"pgen";
+pub const INST_ID: &[u8; 4] = b"inst";
+pub const IBAG_ID: &[u8; 4] = b"ibag";
+pub const IMOD_ID: &[u8; 4] = b"imod";
+pub const IGEN_ID: &[u8; 4] = b"igen";
+pub const SHDR_ID: &[u8; 4] = b"shdr";
+
+pub const PHDR_SIZE: usize = 38;
+pub const BAG_SIZE: usize = 4;
+pub const MOD_SIZE: usize = 10;
+pub const GEN_SIZE: usize = 4;
+pub const INST_SIZE: usize = 22;
+pub const SHDR_SIZE: usize = 46;
+
+/// Number of zero-valued sample points that must follow every sample in `smpl`.
+pub const SAMPLE_GUARD_LEN: usize = 46;
+
+/// Bit of the sample type telling that the sample lives in the ROM named by `irom`.
+pub const ROM_SAMPLE: u16 = 0x8000;
+
+/// Generator operator whose amount is the index of the instrument used by a preset zone.
+pub const GEN_INSTRUMENT: u16 = 41;
+pub const GEN_KEY_RANGE: u16 = 43;
+pub const GEN_VEL_RANGE: u16 = 44;
+/// Generator operator whose amount is the index of the sample used by an instrument zone.
+pub const GEN_SAMPLE_ID: u16 = 53;
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Encodes `value` into a fixed-size, zero-padded name field of a hydra record.
+fn name_field(value: &str) -> [u8; 20] {
+    let mut result = [0; 20];
+    let bytes = value.as_bytes();
+    let len = bytes.len().min(result.len());
+    result[..len].copy_from_slice(&bytes[..len]);
+    result
+}
+
+/// Encodes `value` as a zero-terminated string of even length, as `INFO` subchunks require.
+fn zstr_bytes(value: &str) -> Vec<u8> {
+    let mut result = value.as_bytes().to_vec();
+    result.push(0);
+    if result.len() % 2 == 1 {
+        result.push(0);
+    }
+    result
+}
+
+/// Decodes a string that is terminated by the first zero byte, if any.
+pub(crate) fn read_zstr(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+/// Splits the content of a hydra chunk into records of `size` bytes.
+fn records<'a>(chunk: &Chunk<'a>, size: usize) -> RiffResult<std::slice::ChunksExact<'a, u8>> {
+    let content = chunk.content()?;
+    if content.len() % size != 0 {
+        return Err(chunk.locate(RiffError::malformed(
+            "size is not a multiple of the record size",
+        )));
+    }
+    Ok(content.chunks_exact(size))
+}
+
+/// A `major.minor` version as stored in `ifil` and `iver`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl Version {
+    pub fn from_bytes(data: &[u8]) -> RiffResult<Version> {
+        let data = data
+            .get(0..4)
+            .ok_or_else(|| RiffError::malformed("version too short"))?;
+        Ok(Version {
+            major: u16_at(data, 0),
+            minor: u16_at(data, 2),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let [a, b] = self.major.to_le_bytes();
+        let [c, d] = self.minor.to_le_bytes();
+        [a, b, c, d]
+    }
+}
+
+/// The `INFO` list of a SoundFont.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Info {
+    pub version: Version,
+    pub sound_engine: String,
+    pub name: String,
+    pub rom_name: Option<String>,
+    pub rom_version: Option<Version>,
+    pub creation_date: Option<String>,
+    pub engineers: Option<String>,
+    pub product: Option<String>,
+    pub copyright: Option<String>,
+    pub comments: Option<String>,
+    pub software: Option<String>,
+}
+
+impl Info {
+    pub fn from_chunk(chunk: &Chunk) -> RiffResult<Info> {
+        let mut result = Info::default();
+        for child in chunk.iter()? {
+            let child = child?;
+            let content = child.content()?;
+            match child.id()?.as_bytes() {
+                IFIL_ID => {
+                    result.version = Version::from_bytes(content).map_err(|e| child.locate(e))?
+                }
+                ISNG_ID => result.sound_engine = read_zstr(content),
+                INAM_ID => result.name = read_zstr(content),
+                IROM_ID => result.rom_name = Some(read_zstr(content)),
+                IVER_ID => {
+                    let version = Version::from_bytes(content).map_err(|e| child.locate(e))?;
+                    result.rom_version = Some(version);
+                }
+                ICRD_ID => result.creation_date = Some(read_zstr(content)),
+                IENG_ID => result.engineers = Some(read_zstr(content)),
+                IPRD_ID => result.product = Some(read_zstr(content)),
+                ICOP_ID => result.copyright = Some(read_zstr(content)),
+                ICMT_ID => result.comments = Some(read_zstr(content)),
+                ISFT_ID => result.software = Some(read_zstr(content)),
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Builds the `INFO` list. Only the mandatory `ifil`, `isng` and `INAM` are always written.
+    pub fn to_contents(&self) -> RiffResult<ChunkContents<'static>> {
+        let raw = |id: &[u8; 4], data: Vec<u8>| -> RiffResult<ChunkContents<'static>> {
+            Ok(ChunkContents::RawData(FourCC::new(id)?, Cow::Owned(data)))
+        };
+        let mut children = vec![
+            raw(IFIL_ID, self.version.to_bytes().to_vec())?,
+            raw(ISNG_ID, zstr_bytes(&self.sound_engine))?,
+            raw(INAM_ID, zstr_bytes(&self.name))?,
+        ];
+        if let Some(rom_name) = &self.rom_name {
+            children.push(raw(IROM_ID, zstr_bytes(rom_name))?);
+        }
+        if let Some(rom_version) = &self.rom_version {
+            children.push(raw(IVER_ID, rom_version.to_bytes().to_vec())?);
+        }
+        let optional = [
+            (ICRD_ID, &self.creation_date),
+            (IENG_ID, &self.engineers),
+            (IPRD_ID, &self.product),
+            (ICOP_ID, &self.copyright),
+            (ICMT_ID, &self.comments),
+            (ISFT_ID, &self.software),
+        ];
+        for (id, value) in optional.iter() {
+            if let Some(value) = value {
+                children.push(raw(id, zstr_bytes(value))?);
+            }
+        }
+        Ok(ChunkContents::Children(
+            FourCC::new(b"LIST")?,
+            FourCC::new(INFO_ID)?,
+            children,
+        ))
+    }
+}
+
+/// A `pgen` or `igen` record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Generator {
+    pub operator: u16,
+    /// The raw amount. Depending on the operator it is a signed or unsigned word or a range.
+    pub amount: u16,
+}
+
+impl Generator {
+    pub fn from_bytes(data: &[u8]) -> Generator {
+        Generator {
+            operator: u16_at(data, 0),
+            amount: u16_at(data, 2),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; GEN_SIZE] {
+        let [a, b] = self.operator.to_le_bytes();
+        let [c, d] = self.amount.to_le_bytes();
+        [a, b, c, d]
+    }
+
+    pub fn amount_i16(&self) -> i16 {
+        self.amount as i16
+    }
+
+    /// Interprets the amount as a `(low, high)` key or velocity range.
+    pub fn amount_range(&self) -> (u8, u8) {
+        let [low, high] = self.amount.to_le_bytes();
+        (low, high)
+    }
+}
+
+/// A `pmod` or `imod` record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modulator {
+    pub source: u16,
+    pub destination: u16,
+    pub amount: i16,
+    pub amount_source: u16,
+    pub transform: u16,
+}
+
+impl Modulator {
+    pub fn from_bytes(data: &[u8]) -> Modulator {
+        Modulator {
+            source: u16_at(data, 0),
+            destination: u16_at(data, 2),
+            amount: u16_at(data, 4) as i16,
+            amount_source: u16_at(data, 6),
+            transform: u16_at(data, 8),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; MOD_SIZE] {
+        let mut result = [0; MOD_SIZE];
+        result[0..2].copy_from_slice(&self.source.to_le_bytes());
+        result[2..4].copy_from_slice(&self.destination.to_le_bytes());
+        result[4..6].copy_from_slice(&self.amount.to_le_bytes());
+        result[6..8].copy_from_slice(&self.amount_source.to_le_bytes());
+        result[8..10].copy_from_slice(&self.transform.to_le_bytes());
+        result
+    }
+}
+
+/// A preset or instrument zone, i.e. a bag resolved into its generators and modulators.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Zone {
+    pub generators: Vec<Generator>,
+    pub modulators: Vec<Modulator>,
+}
+
+impl Zone {
+    fn generator(&self, operator: u16) -> Option<&Generator> {
+        self.generators.iter().find(|g| g.operator == operator)
+    }
+
+    /// The index of the instrument a preset zone refers to. A zone without one is global.
+    pub fn instrument(&self) -> Option<usize> {
+        self.generator(GEN_INSTRUMENT).map(|g| g.amount as usize)
+    }
+
+    /// The index of the sample an instrument zone refers to. A zone without one is global.
+    pub fn sample(&self) -> Option<usize> {
+        self.generator(GEN_SAMPLE_ID).map(|g| g.amount as usize)
+    }
+
+    pub fn key_range(&self) -> Option<(u8, u8)> {
+        self.generator(GEN_KEY_RANGE).map(|g| g.amount_range())
+    }
+
+    pub fn velocity_range(&self) -> Option<(u8, u8)> {
+        self.generator(GEN_VEL_RANGE).map(|g| g.amount_range())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    pub preset: u16,
+    pub bank: u16,
+    pub library: u32,
+    pub genre: u32,
+    pub morphology: u32,
+    pub zones: Vec<Zone>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Instrument {
+    pub name: String,
+    pub zones: Vec<Zone>,
+}
+
+/// A sample header together with its audio.
+///
+/// Unlike the `shdr` record, loop points are relative to the start of `data`, so that a
+/// `Sample` does not depend on where it lives inside `smpl`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sample<'a> {
+    pub name: String,
+    pub loop_start: u32,
+    pub loop_end: u32,
+    pub sample_rate: u32,
+    pub original_pitch: u8,
+    pub pitch_correction: i8,
+    /// Index of the linked sample of a stereo pair or linked chain.
+    pub sample_link: u16,
+    pub sample_type: u16,
+    /// 16-bit little-endian PCM, borrowed from `smpl` when read from a file.
+    pub data: Cow<'a, [u8]>,
+    /// The low bytes of 24-bit samples, borrowed from `sm24` when present.
+    pub data_24: Option<Cow<'a, [u8]>>,
+    /// For a ROM sample, which has no `data`, the sample points it spans in the ROM.
+    pub rom_range: Option<Range<u32>>,
+}
+
+impl<'a> Sample<'a> {
+    /// The number of sample points.
+    pub fn len(&self) -> usize {
+        self.data.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// A parsed `RIFF sfbk` file.
+///
+/// The hydra of `pdta` is resolved into a graph: presets own their zones, preset zones refer to
+/// `instruments` by index and instrument zones refer to `samples` by index. The terminal
+/// records (`EOP`, `EOI` and `EOS`) are dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SoundFont<'a> {
+    pub info: Info,
+    pub presets: Vec<Preset>,
+    pub instruments: Vec<Instrument>,
+    pub samples: Vec<Sample<'a>>,
+}
+
+/// The flattened `bag`, `mod` and `gen` records of a list of presets or instruments.
+#[derive(Default)]
+struct Hydra {
+    bags: Vec<u8>,
+    modulators: Vec<u8>,
+    generators: Vec<u8>,
+    bag_count: usize,
+    modulator_count: usize,
+    generator_count: usize,
+}
+
+impl Hydra {
+    /// Appends the zones of one preset or instrument and returns the index of its first bag.
+    fn push_zones(&mut self, zones: &[Zone]) -> RiffResult<u16> {
+        let first_bag = u16::try_from(self.bag_count)?;
+        for zone in zones {
+            self.push_bag()?;
+            for generator in &zone.generators {
+                self.generators.extend_from_slice(&generator.to_bytes());
+            }
+            for modulator in &zone.modulators {
+                self.modulators.extend_from_slice(&modulator.to_bytes());
+            }
+            self.generator_count += zone.generators.len();
+            self.modulator_count += zone.modulators.len();
+        }
+        Ok(first_bag)
+    }
+
+    fn push_bag(&mut self) -> RiffResult<()> {
+        self.bags
+            .extend_from_slice(&u16::try_from(self.generator_count)?.to_le_bytes());
+        self.bags
+            .extend_from_slice(&u16::try_from(self.modulator_count)?.to_le_bytes());
+        self.bag_count += 1;
+        Ok(())
+    }
+
+    /// Appends the terminal bag, modulator and generator records.
+    fn finish(mut self) -> RiffResult<Hydra> {
+        self.push_bag()?;
+        self.modulators.extend_from_slice(&[0; MOD_SIZE]);
+        self.generators.extend_from_slice(&[0; GEN_SIZE]);
+        Ok(self)
+    }
+}
+
+/// Resolves the bags in `bag_indices` into zones.
+fn zones(
+    bag_indices: std::ops::Range<usize>,
+    bags: &[(usize, usize)],
+    generators: &[Generator],
+    modulators: &[Modulator],
+) -> RiffResult<Vec<Zone>> {
+    let out_of_range = || RiffError::malformed("bag index out of range");
+    bag_indices
+        .map(|idx| {
+            let (gen_begin, mod_begin) = *bags.get(idx).ok_or_else(out_of_range)?;
+            let (gen_end, mod_end) = *bags.get(idx + 1).ok_or_else(out_of_range)?;
+            Ok(Zone {
+                generators: generators
+                    .get(gen_begin..gen_end)
+                    .ok_or_else(out_of_range)?
+                    .to_vec(),
+                modulators: modulators
+                    .get(mod_begin..mod_end)
+                    .ok_or_else(out_of_range)?
+                    .to_vec(),
+            })
+        })
+        .collect()
+}
+
+fn bags(chunk: &Chunk) -> RiffResult<Vec<(usize, usize)>> {
+    Ok(records(chunk, BAG_SIZE)?
+        .map(|r| (u16_at(r, 0) as usize, u16_at(r, 2) as usize))
+        .collect())
+}
+
+impl<'a> SoundFont<'a> {
+    pub fn from_chunk(chunk: &Chunk<'a>) -> RiffResult<SoundFont<'a>> {
+        chunk.expect_form_type(SFBK_ID)?;
+        let mut info = None;
+        let mut smpl: &[u8] = &[];
+        let mut sm24 = None;
+        let mut pdta = None;
+        for child in chunk.iter()? {
+            let child = child?;
+            if child.kind() == ChunkKind::List {
+                match child.chunk_type()?.as_bytes() {
+                    INFO_ID => info = Some(Info::from_chunk(&child)?),
+                    SDTA_ID => {
+                        for sample_data in child.iter()? {
+                            let sample_data = sample_data?;
+                            match sample_data.id()?.as_bytes() {
+                                SMPL_ID => smpl = sample_data.content()?,
+                                SM24_ID => sm24 = Some(sample_data.content()?),
+                                _ => {}
+                            }
+                        }
+                    }
+                    PDTA_ID => pdta = Some(child),
+                    _ => {}
+                }
+            }
+        }
+        let info = info.ok_or_else(|| chunk.locate(RiffError::malformed("missing INFO list")))?;
+        let pdta = pdta.ok_or_else(|| chunk.locate(RiffError::malformed("missing pdta list")))?;
+
+        let mut phdr = Vec::new();
+        let mut pbag = Vec::new();
+        let mut pmod = Vec::new();
+        let mut pgen = Vec::new();
+        let mut inst = Vec::new();
+        let mut ibag = Vec::new();
+        let mut imod = Vec::new();
+        let mut igen = Vec::new();
+        let mut shdr = Vec::new();
+        for child in pdta.iter()? {
+            let child = child?;
+            match child.id()?.as_bytes() {
+                PHDR_ID => phdr = records(&child, PHDR_SIZE)?.collect(),
+                PBAG_ID => pbag = bags(&child)?,
+                PMOD_ID => {
+                    pmod = records(&child, MOD_SIZE)?
+                        .map(Modulator::from_bytes)
+                        .collect()
+                }
+                PGEN_ID => {
+                    pgen = records(&child, GEN_SIZE)?
+                        .map(Generator::from_bytes)
+                        .collect()
+                }
+                INST_ID => inst = records(&child, INST_SIZE)?.collect(),
+                IBAG_ID => ibag = bags(&child)?,
+                IMOD_ID => {
+                    imod = records(&child, MOD_SIZE)?
+                        .map(Modulator::from_bytes)
+                        .collect()
+                }
+                IGEN_ID => {
+                    igen = records(&child, GEN_SIZE)?
+                        .map(Generator::from_bytes)
+                        .collect()
+                }
+                SHDR_ID => shdr = records(&child, SHDR_SIZE)?.collect(),
+                _ => {}
+            }
+        }
+
+        let presets = phdr
+            .windows(2)
+            .map(|pair| {
+                let (record, next) = (pair[0], pair[1]);
+                let bag_indices = u16_at(record, 24) as usize..u16_at(next, 24) as usize;
+                Ok(Preset {
+                    name: read_zstr(&record[0..20]),
+                    preset: u16_at(record, 20),
+                    bank: u16_at(record, 22),
+                    library: u32_at(record, 26),
+                    genre: u32_at(record, 30),
+                    morphology: u32_at(record, 34),
+                    zones: zones(bag_indices, &pbag, &pgen, &pmod)?,
+                })
+            })
+            .collect::<RiffResult<_>>()
+            .map_err(|e| pdta.locate(e))?;
+
+        let instruments = inst
+            .windows(2)
+            .map(|pair| {
+                let (record, next) = (pair[0], pair[1]);
+                let bag_indices = u16_at(record, 20) as usize..u16_at(next, 20) as usize;
+                Ok(Instrument {
+                    name: read_zstr(&record[0..20]),
+                    zones: zones(bag_indices, &ibag, &igen, &imod)?,
+                })
+            })
+            .collect::<RiffResult<_>>()
+            .map_err(|e| pdta.locate(e))?;
+
+        let samples = shdr
+            .iter()
+            .take(shdr.len().saturating_sub(1))
+            .map(|record| {
+                let start = u32_at(record, 20) as usize;
+                let end = u32_at(record, 24) as usize;
+                let sample_type = u16_at(record, 44);
+                let is_rom = sample_type & ROM_SAMPLE != 0;
+                let data = if is_rom {
+                    &[]
+                } else {
+                    smpl.get(start * 2..end * 2)
+                        .ok_or_else(|| RiffError::malformed("sample outside of smpl"))?
+                };
+                let data_24 = match sm24 {
+                    Some(_) if is_rom => None,
+                    Some(sm24) => {
+                        Some(Cow::Borrowed(sm24.get(start..end).ok_or_else(|| {
+                            RiffError::malformed("sample outside of sm24")
+                        })?))
+                    }
+                    None => None,
+                };
+                Ok(Sample {
+                    name: read_zstr(&record[0..20]),
+                    loop_start: u32_at(record, 28).wrapping_sub(start as u32),
+                    loop_end: u32_at(record, 32).wrapping_sub(start as u32),
+                    sample_rate: u32_at(record, 36),
+                    original_pitch: record[40],
+                    pitch_correction: record[41] as i8,
+                    sample_link: u16_at(record, 42),
+                    sample_type,
+                    data: Cow::Borrowed(data),
+                    data_24,
+                    rom_range: Some(start as u32..end as u32).filter(|_| is_rom),
+                })
+            })
+            .collect::<RiffResult<_>>()
+            .map_err(|e| pdta.locate(e))?;
+
+        Ok(SoundFont {
+            info,
+            presets,
+            instruments,
+            samples,
+        })
+    }
+
+    /// The instrument used by a preset zone.
+    pub fn instrument(&self, zone: &Zone) -> Option<&Instrument> {
+        zone.instrument().and_then(|idx| self.instruments.get(idx))
+    }
+
+    /// The sample used by an instrument zone.
+    pub fn sample(&self, zone: &Zone) -> Option<&Sample<'a>> {
+        zone.sample().and_then(|idx| self.samples.get(idx))
+    }
+
+    /// Builds a `RIFF sfbk` tree from this bank.
+    ///
+    /// Sample data is laid out back to back in `smpl`, each sample followed by
+    /// `SAMPLE_GUARD_LEN` zero points, and the `EOP`, `EOI` and `EOS` terminal records are
+    /// generated. ROM samples keep their `rom_range` and take no space in `smpl`. An `sm24` chunk
+    /// is written when any sample carries 24-bit data, using zeros for samples that do not.
+    pub fn to_contents(&self) -> RiffResult<ChunkContents<'static>> {
+        let list = |chunk_type: &[u8; 4], children| -> RiffResult<ChunkContents<'static>> {
+            Ok(ChunkContents::Children(
+                FourCC::new(b"LIST")?,
+                FourCC::new(chunk_type)?,
+                children,
+            ))
+        };
+        let raw = |id: &[u8; 4], data: Vec<u8>| -> RiffResult<ChunkContents<'static>> {
+            Ok(ChunkContents::RawData(FourCC::new(id)?, Cow::Owned(data)))
+        };
+
+        let has_24_bit = self.samples.iter().any(|s| s.data_24.is_some());
+        let mut smpl = Vec::new();
+        let mut sm24 = Vec::new();
+        let mut shdr = Vec::new();
+        for sample in &self.samples {
+            let (start, end) = match &sample.rom_range {
+                Some(range) => (range.start, range.end),
+                None => {
+                    let start = u32::try_from(smpl.len() / 2)?;
+                    let end = start + u32::try_from(sample.len())?;
+                    smpl.extend_from_slice(&sample.data[..sample.len() * 2]);
+                    smpl.extend_from_slice(&[0; SAMPLE_GUARD_LEN * 2]);
+                    if has_24_bit {
+                        match &sample.data_24 {
+                            Some(data_24) => sm24.extend_from_slice(data_24),
+                            None => sm24.resize(sm24.len() + sample.len(), 0),
+                        }
+                        sm24.resize(smpl.len() / 2, 0);
+                    }
+                    (start, end)
+                }
+            };
+            shdr.extend_from_slice(&name_field(&sample.name));
+            shdr.extend_from_slice(&start.to_le_bytes());
+            shdr.extend_from_slice(&end.to_le_bytes());
+            shdr.extend_from_slice(&sample.loop_start.wrapping_add(start).to_le_bytes());
+            shdr.extend_from_slice(&sample.loop_end.wrapping_add(start).to_le_bytes());
+            shdr.extend_from_slice(&sample.sample_rate.to_le_bytes());
+            shdr.push(sample.original_pitch);
+            shdr.push(sample.pitch_correction as u8);
+            shdr.extend_from_slice(&sample.sample_link.to_le_bytes());
+            shdr.extend_from_slice(&sample.sample_type.to_le_bytes());
+        }
+        shdr.extend_from_slice(&name_field("EOS"));
+        shdr.resize(shdr.len() + SHDR_SIZE - 20, 0);
+
+        let mut preset_hydra = Hydra::default();
+        let mut phdr = Vec::new();
+        for preset in &self.presets {
+            let first_bag = preset_hydra.push_zones(&preset.zones)?;
+            phdr.extend_from_slice(&name_field(&preset.name));
+            phdr.extend_from_slice(&preset.preset.to_le_bytes());
+            phdr.extend_from_slice(&preset.bank.to_le_bytes());
+            phdr.extend_from_slice(&first_bag.to_le_bytes());
+            phdr.extend_from_slice(&preset.library.to_le_bytes());
+            phdr.extend_from_slice(&preset.genre.to_le_bytes());
+            phdr.extend_from_slice(&preset.morphology.to_le_bytes());
+        }
+        phdr.extend_from_slice(&name_field("EOP"));
+        phdr.extend_from_slice(&[0; 4]);
+        phdr.extend_from_slice(&u16::try_from(preset_hydra.bag_count)?.to_le_bytes());
+        phdr.extend_from_slice(&[0; 12]);
+        let preset_hydra = preset_hydra.finish()?;
+
+        let mut instrument_hydra = Hydra::default();
+        let mut inst = Vec::new();
+        for instrument in &self.instruments {
+            let first_bag = instrument_hydra.push_zones(&instrument.zones)?;
+            inst.extend_from_slice(&name_field(&instrument.name));
+            inst.extend_from_slice(&first_bag.to_le_bytes());
+        }
+        inst.extend_from_slice(&name_field("EOI"));
+        inst.extend_from_slice(&u16::try_from(instrument_hydra.bag_count)?.to_le_bytes());
+        let instrument_hydra = instrument_hydra.finish()?;
+
+        let mut sdta = vec![raw(SMPL_ID, smpl)?];
+        if has_24_bit {
+            sdta.push(raw(SM24_ID, sm24)?);
+        }
+        let pdta = vec![
+            raw(PHDR_ID, phdr)?,
+            raw(PBAG_ID, preset_hydra.bags)?,
+            raw(PMOD_ID, preset_hydra.modulators)?,
+            raw(PGEN_ID, preset_hydra.generators)?,
+            raw(INST_ID, inst)?,
+            raw(IBAG_ID, instrument_hydra.bags)?,
+            raw(IMOD_ID, instrument_hydra.modulators)?,
+            raw(IGEN_ID, instrument_hydra.generators)?,
+            raw(SHDR_ID, shdr)?,
+        ];
+        Ok(ChunkContents::Children(
+            FourCC::new(b"RIFF")?,
+            FourCC::new(SFBK_ID)?,
+            vec![
+                self.info.to_contents()?,
+                list(SDTA_ID, sdta)?,
+                list(PDTA_ID, pdta)?,
+            ],
+        ))
+    }
+
+    /// Writes this bank as a `RIFF sfbk` file and returns the number of bytes written.
+    pub fn write<W>(&self, writer: &mut W) -> RiffResult<u64>
+    where
+        W: Write,
+    {
+        self.to_contents()?.write(writer)
+    }
+}
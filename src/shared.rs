@@ -0,0 +1,84 @@
+use crate::{
+    error::RiffResult,
+    options::ParseContext,
+    riff::{Chunk, Frame, Riff},
+    RiffError,
+};
+use std::sync::Arc;
+
+/// A chunk of a `Riff` that holds on to the file, so it can outlive the borrow a `Chunk` needs
+/// and be moved to other threads, such as the tasks of a thread pool.
+///
+/// The chunk is rebuilt by `chunk` without parsing anything again, and shares the warnings of
+/// the parse it came from.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{shared::SharedChunk, Riff};
+/// use std::sync::Arc;
+/// let file = Arc::new(Riff::from_path("test_assets/sample.avi").unwrap());
+/// let handles: Vec<_> = SharedChunk::root(file).unwrap().children().unwrap();
+/// let lens: Vec<_> = std::thread::spawn(move || {
+///     handles
+///         .iter()
+///         .map(|handle| handle.chunk().payload_len().unwrap())
+///         .collect()
+/// })
+/// .join()
+/// .unwrap();
+/// assert_eq!(lens, [2004, 2000900, 7648]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedChunk {
+    riff: Arc<Riff>,
+    /// The chunks holding this one, from the root down.
+    ancestors: Vec<Frame>,
+    frame: Frame,
+    context: Arc<ParseContext>,
+}
+
+impl SharedChunk {
+    /// The root chunk of `riff`.
+    pub fn root(riff: Arc<Riff>) -> RiffResult<SharedChunk> {
+        let root = riff.as_chunk()?;
+        SharedChunk::new(riff.clone(), &root)
+    }
+
+    /// Holds on to `chunk`, which must have been parsed from `riff`.
+    pub fn new(riff: Arc<Riff>, chunk: &Chunk<'_>) -> RiffResult<SharedChunk> {
+        let mut ancestors = chunk.frames_within(riff.as_bytes()).unwrap_or_default();
+        let frame = ancestors.pop().ok_or_else(|| RiffError::MalformedChunk {
+            location: chunk.location(),
+            reason: "chunk does not belong to this file",
+        })?;
+        Ok(SharedChunk {
+            context: chunk.context().clone(),
+            riff,
+            ancestors,
+            frame,
+        })
+    }
+
+    /// The file this chunk belongs to.
+    pub fn riff(&self) -> &Arc<Riff> {
+        &self.riff
+    }
+
+    pub fn chunk(&self) -> Chunk<'_> {
+        Chunk::from_frames(
+            self.riff.as_bytes(),
+            &self.ancestors,
+            &self.frame,
+            &self.context,
+        )
+    }
+
+    /// The chunks held by this one, in order.
+    pub fn children(&self) -> RiffResult<Vec<SharedChunk>> {
+        self.chunk()
+            .iter()?
+            .map(|child| SharedChunk::new(self.riff.clone(), &child?))
+            .collect()
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    avi::{MainHeader, AVIH_ID},
+    dls::{WaveFormat, FMT_ID},
+    error::RiffResult,
+    webp::{Vp8x, VP8X_ID},
+    writer::ChunkContents,
+    Chunk, ChunkCodec, ChunkKind, FourCC,
+};
+use std::borrow::Cow;
+
+const INFO_ID: &[u8; 4] = b"INFO";
+
+/// An owned copy of a chunk and everything it holds, which can be written back.
+///
+/// With the `serde` feature, trees can be serialized, for example to JSON, and deserialized
+/// back. Payloads are rendered as hexadecimal, except for the strings of `LIST INFO` chunks
+/// which are rendered as text, and the `fmt `, `avih` and `VP8X` chunks which are decoded into
+/// their fields. Writing a tree copied from a well-formed file reproduces the file byte for
+/// byte.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{tree::{Node, Payload}, Chunk};
+/// let bytes = b"RIFF\x1a\0\0\0WAVELIST\x0e\0\0\0INFOINAM\x02\0\0\0a\0";
+/// let node = Node::from_chunk(&Chunk::from_bytes(bytes).unwrap()).unwrap();
+/// match &node {
+///     Node::List { children, .. } => match &children[0] {
+///         Node::List { children, .. } => match &children[0] {
+///             Node::Raw { payload, .. } => assert_eq!(*payload, Payload::Text("a".into())),
+///             _ => unreachable!(),
+///         },
+///         _ => unreachable!(),
+///     },
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(node.to_bytes().unwrap(), &bytes[..]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum Node {
+    Raw {
+        id: FourCC,
+        payload: Payload,
+    },
+    List {
+        id: FourCC,
+        form_type: FourCC,
+        children: Vec<Node>,
+    },
+    Seqt {
+        id: FourCC,
+        children: Vec<Node>,
+    },
+}
+
+/// The payload of a raw chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Payload {
+    Bytes(#[cfg_attr(feature = "serde", serde(with = "hex"))] Vec<u8>),
+    /// Printable ASCII, stored followed by a NUL byte.
+    Text(String),
+    /// The payload of a `fmt ` chunk, followed by any format-specific extension.
+    Format {
+        format: WaveFormat,
+        #[cfg_attr(feature = "serde", serde(with = "hex"))]
+        extension: Vec<u8>,
+    },
+    /// The payload of an `avih` chunk.
+    MainHeader(MainHeader),
+    /// The payload of a `VP8X` chunk.
+    Vp8x(Vp8x),
+}
+
+impl Payload {
+    /// Decodes `content`, the payload of a raw chunk identified by `id`, if its layout is
+    /// known and writing it back gives the same bytes.
+    fn typed(id: &[u8; 4], content: &[u8]) -> Option<Payload> {
+        let payload = match id {
+            FMT_ID => Payload::Format {
+                format: WaveFormat::decode(content).ok()?,
+                extension: content[WaveFormat::LEN..].to_vec(),
+            },
+            AVIH_ID if content.len() == MainHeader::LEN => {
+                Payload::MainHeader(MainHeader::decode(content).ok()?)
+            }
+            VP8X_ID => Payload::Vp8x(Vp8x::from_bytes(content).ok()?),
+            _ => return None,
+        };
+        Some(payload).filter(|payload| payload.to_bytes().is_ok_and(|bytes| bytes == content))
+    }
+
+    /// Fails if a decoded payload holds values that cannot be written, such as a `VP8X` canvas
+    /// of zero pixels.
+    pub fn to_bytes(&self) -> RiffResult<Vec<u8>> {
+        let bytes = match self {
+            Payload::Bytes(bytes) => bytes.clone(),
+            Payload::Text(text) => {
+                let mut bytes = text.as_bytes().to_vec();
+                bytes.push(0);
+                bytes
+            }
+            Payload::Format { format, extension } => {
+                let mut bytes = Vec::new();
+                format.encode(&mut bytes)?;
+                bytes.extend_from_slice(extension);
+                bytes
+            }
+            Payload::MainHeader(header) => {
+                let mut bytes = Vec::new();
+                header.encode(&mut bytes)?;
+                bytes
+            }
+            Payload::Vp8x(vp8x) => vp8x.to_bytes()?.to_vec(),
+        };
+        Ok(bytes)
+    }
+}
+
+impl Node {
+    /// Copies `chunk` and everything it holds.
+    pub fn from_chunk(chunk: &Chunk<'_>) -> RiffResult<Node> {
+        let id = chunk.id()?;
+        let node = match chunk.kind() {
+            ChunkKind::Raw => {
+                let content = chunk.content()?;
+                let in_info = match chunk.parent() {
+                    Some(parent) => parent.is_list_of(INFO_ID)?,
+                    None => false,
+                };
+                let payload = match content.split_last() {
+                    Some((0, text)) if in_info && text.iter().all(|b| (0x20..0x7f).contains(b)) => {
+                        Payload::Text(String::from_utf8_lossy(text).into_owned())
+                    }
+                    _ => Payload::typed(id.as_bytes(), content)
+                        .unwrap_or_else(|| Payload::Bytes(content.to_vec())),
+                };
+                Node::Raw { id, payload }
+            }
+            ChunkKind::List => Node::List {
+                id,
+                form_type: chunk.chunk_type()?,
+                children: Node::children(chunk)?,
+            },
+            ChunkKind::Seqt => Node::Seqt {
+                id,
+                children: Node::children(chunk)?,
+            },
+        };
+        Ok(node)
+    }
+
+    fn children(chunk: &Chunk<'_>) -> RiffResult<Vec<Node>> {
+        chunk
+            .iter()?
+            .map(|child| Node::from_chunk(&child?))
+            .collect()
+    }
+
+    pub fn id(&self) -> FourCC {
+        match self {
+            Node::Raw { id, .. } | Node::List { id, .. } | Node::Seqt { id, .. } => *id,
+        }
+    }
+
+    /// The tree, ready to be written.
+    pub fn to_contents(&self) -> RiffResult<ChunkContents<'_>> {
+        let contents = match self {
+            Node::Raw { id, payload } => {
+                let payload = match payload {
+                    Payload::Bytes(bytes) => Cow::Borrowed(&bytes[..]),
+                    _ => Cow::Owned(payload.to_bytes()?),
+                };
+                ChunkContents::RawData(*id, payload)
+            }
+            Node::List {
+                id,
+                form_type,
+                children,
+            } => ChunkContents::Children(*id, *form_type, Node::children_contents(children)?),
+            Node::Seqt { id, children } => {
+                ChunkContents::ChildrenNoType(*id, Node::children_contents(children)?)
+            }
+        };
+        Ok(contents)
+    }
+
+    fn children_contents(children: &[Node]) -> RiffResult<Vec<ChunkContents<'_>>> {
+        children.iter().map(Node::to_contents).collect()
+    }
+
+    pub fn to_bytes(&self) -> RiffResult<Vec<u8>> {
+        self.to_contents()?.to_bytes()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut result = String::with_capacity(bytes.len() * 2);
+        for byte in bytes {
+            result.push_str(&format!("{:02x}", byte));
+        }
+        serializer.serialize_str(&result)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        if text.len() % 2 != 0 {
+            return Err(D::Error::custom("hexadecimal payload has an odd length"));
+        }
+        (0..text.len())
+            .step_by(2)
+            .map(|i| {
+                text.get(i..i + 2)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| D::Error::custom("invalid hexadecimal payload"))
+            })
+            .collect()
+    }
+}
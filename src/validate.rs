@@ -0,0 +1,258 @@
+use crate::{
+    error::Location,
+    fourcc::is_printable,
+    options::{ParseMode, ParseOptions, WarningKind},
+    registry::Registry,
+    Chunk, ChunkKind, FourCC,
+};
+use std::fmt;
+
+/// A problem found by `validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// The size field of the chunk extends past the end of its parent.
+    SizeExceedsParent { declared: u32, available: u32 },
+    /// A `RIFF` or `LIST` chunk holding no chunks.
+    EmptyList,
+    /// An identifier or form type holding bytes other than printable ASCII.
+    NonPrintableFourCC { bytes: [u8; 4] },
+    /// The chunk has an odd size but its pad byte is missing.
+    MissingPadding,
+    /// The pad byte of the chunk is not zero.
+    NonZeroPadding { value: u8 },
+    /// A chunk that may appear only once among its siblings appears again.
+    DuplicateSingleton { id: [u8; 4] },
+    /// The root chunk has a form type this crate does not know.
+    UnknownFormType { found: [u8; 4] },
+    /// Bytes that do not form a chunk, at the end of a list or after the root chunk.
+    TrailingBytes { len: usize },
+    /// A chunk could not be parsed, so its contents were not checked.
+    Unparsable { reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub location: Location,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            IssueKind::SizeExceedsParent {
+                declared,
+                available,
+            } => write!(
+                f,
+                "size of {} bytes exceeds the {} bytes available",
+                declared, available
+            )?,
+            IssueKind::EmptyList => write!(f, "list holds no chunks")?,
+            IssueKind::NonPrintableFourCC { bytes } => {
+                write!(f, "non-printable FourCC {:?}", bytes)?
+            }
+            IssueKind::MissingPadding => write!(f, "missing pad byte")?,
+            IssueKind::NonZeroPadding { value } => write!(f, "pad byte is {:#04x}", value)?,
+            IssueKind::DuplicateSingleton { id } => {
+                write!(f, "duplicate {:?} chunk", String::from_utf8_lossy(id))?
+            }
+            IssueKind::UnknownFormType { found } => {
+                write!(f, "unknown form type {:?}", String::from_utf8_lossy(found))?
+            }
+            IssueKind::TrailingBytes { len } => write!(f, "{} trailing bytes", len)?,
+            IssueKind::Unparsable { reason } => write!(f, "unparsable chunk: {}", reason)?,
+        }
+        write!(f, " at {}", self.location)
+    }
+}
+
+/// Every problem found in a file, ordered by offset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn push(&mut self, location: Location, kind: IssueKind) {
+        self.issues.push(Issue { location, kind });
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the structure of the RIFF file held in `data`, reporting every problem at once
+/// rather than stopping at the first one.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::validate::{validate, IssueKind};
+/// let report = validate(b"RIFF\x04\0\0\0WAVE");
+/// assert_eq!(report.issues.len(), 1);
+/// assert_eq!(report.issues[0].kind, IssueKind::EmptyList);
+/// ```
+pub fn validate(data: &[u8]) -> Report {
+    validate_with_registry(data, &Registry::new())
+}
+
+/// Checks the structure of the RIFF file held in `data`, using `registry` to tell which form
+/// types are known, which chunks hold other chunks and which may appear only once.
+pub fn validate_with_registry(data: &[u8], registry: &Registry) -> Report {
+    validate_with_options(
+        data,
+        registry,
+        ParseOptions::lenient().with_registry(registry),
+    )
+}
+
+/// Checks the structure of the RIFF file held in `data`, reading the containers registered in
+/// `options` as holding other chunks.
+///
+/// The file is always parsed leniently, whatever the mode of `options`, so that every issue is
+/// reported.
+pub fn validate_with_options(data: &[u8], registry: &Registry, options: ParseOptions) -> Report {
+    let mut options = options;
+    options.mode = ParseMode::Lenient;
+    let mut report = Report::default();
+    let root = match Chunk::from_bytes_with_options(data, options) {
+        Ok(root) => root,
+        Err(err) => {
+            let location = err.location().cloned().unwrap_or_default();
+            report.push(
+                location,
+                IssueKind::Unparsable {
+                    reason: err.to_string(),
+                },
+            );
+            return report;
+        }
+    };
+    let mut form_type = None;
+    if root.kind() == ChunkKind::List {
+        if let Ok(found) = root.chunk_type() {
+            if registry.form_type(found).is_none() {
+                let found = found.into_bytes();
+                report.push(root.location(), IssueKind::UnknownFormType { found });
+            }
+            form_type = Some(found);
+        }
+    }
+    check_chunk(&root, registry, form_type, &mut report);
+    let trailing = data.len() - root.as_bytes().len();
+    if trailing > 0 {
+        report.push(
+            Location::new(root.as_bytes().len(), String::new()),
+            IssueKind::TrailingBytes { len: trailing },
+        );
+    }
+    for warning in root.warnings() {
+        let kind = match warning.kind {
+            WarningKind::SizeClamped {
+                declared,
+                available,
+            } => IssueKind::SizeExceedsParent {
+                declared,
+                available,
+            },
+            WarningKind::MissingPadding => IssueKind::MissingPadding,
+            WarningKind::TrailingBytes { len } => IssueKind::TrailingBytes { len },
+        };
+        report.push(warning.location, kind);
+    }
+    report.issues.sort_by_key(|issue| issue.location.offset);
+    report
+}
+
+fn check_chunk(
+    chunk: &Chunk<'_>,
+    registry: &Registry,
+    form_type: Option<FourCC>,
+    report: &mut Report,
+) {
+    let bytes = chunk.as_bytes();
+    let mut id = [0; 4];
+    id.copy_from_slice(&bytes[0..4]);
+    if !is_printable(&id) {
+        report.push(
+            chunk.location(),
+            IssueKind::NonPrintableFourCC { bytes: id },
+        );
+    }
+    if let Ok(payload_len) = chunk.payload_len() {
+        if let Some(&value) = bytes.get(8 + payload_len as usize) {
+            if value != 0 {
+                report.push(chunk.location(), IssueKind::NonZeroPadding { value });
+            }
+        }
+    }
+    if chunk.kind() == ChunkKind::Raw {
+        return;
+    }
+    if chunk.kind() == ChunkKind::List {
+        if let Ok(form_type) = chunk.chunk_type() {
+            let form_type = form_type.into_bytes();
+            if !is_printable(&form_type) {
+                report.push(
+                    chunk.location(),
+                    IssueKind::NonPrintableFourCC { bytes: form_type },
+                );
+            }
+        }
+    }
+    let children = match chunk.iter() {
+        Ok(children) => children,
+        Err(_) => return,
+    };
+    if chunk.kind() == ChunkKind::List && chunk.payload_len().ok() == Some(4) {
+        report.push(chunk.location(), IssueKind::EmptyList);
+    }
+    let mut seen: Vec<[u8; 4]> = Vec::new();
+    for child in children {
+        let child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                let location = err.location().cloned().unwrap_or_default();
+                report.push(
+                    location,
+                    IssueKind::Unparsable {
+                        reason: err.to_string(),
+                    },
+                );
+                break;
+            }
+        };
+        // Lists are registered, and reported, by their list type.
+        let child_id = match child.kind() {
+            ChunkKind::List => child.chunk_type(),
+            _ => child.id(),
+        };
+        let singleton = child_id
+            .as_ref()
+            .ok()
+            .and_then(|&id| registry.lookup(form_type, id))
+            .is_some_and(|info| info.singleton);
+        if singleton {
+            let child_id = child_id.map(|id| id.into_bytes()).unwrap_or_default();
+            if seen.contains(&child_id) {
+                report.push(
+                    child.location(),
+                    IssueKind::DuplicateSingleton { id: child_id },
+                );
+            } else {
+                seen.push(child_id);
+            }
+        }
+        check_chunk(&child, registry, form_type, report);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{error::RiffResult, Chunk, ChunkIter, ChunkKind};
+
+/// A chunk reached by a walk.
+#[derive(Debug, Clone)]
+pub struct Entry<'a> {
+    /// The number of containers between the chunk and the start of the walk, which is at 0.
+    pub depth: usize,
+    /// The path of the chunk, such as `RIFF:AVI /LIST:hdrl/avih`.
+    pub path: String,
+    /// The absolute offset of the chunk within the buffer it was parsed from.
+    pub offset: usize,
+    pub chunk: Chunk<'a>,
+}
+
+impl<'a> Entry<'a> {
+    fn new(depth: usize, chunk: Chunk<'a>) -> Entry<'a> {
+        Entry {
+            depth,
+            path: chunk.path(),
+            offset: chunk.offset(),
+            chunk,
+        }
+    }
+
+    /// Whether the chunk holds other chunks, as `RIFF`, `LIST` and `seqt` do.
+    pub fn is_container(&self) -> bool {
+        self.chunk.kind() != ChunkKind::Raw
+    }
+}
+
+/// A depth-first walk over a chunk and everything below it, returned by `Chunk::walk`.
+///
+/// Every chunk is yielded before its children.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::Chunk;
+/// let bytes = b"RIFF\x1a\0\0\0smplLIST\x0e\0\0\0tst1test\x02\0\0\0ab";
+/// let chunk = Chunk::from_bytes(bytes).unwrap();
+/// let paths: Vec<_> = chunk.walk().map(|entry| entry.unwrap().path).collect();
+/// assert_eq!(paths, ["RIFF:smpl", "RIFF:smpl/LIST:tst1", "RIFF:smpl/LIST:tst1/test"]);
+/// ```
+#[derive(Debug)]
+pub struct Walk<'a> {
+    root: Option<Chunk<'a>>,
+    /// The container yielded last, whose children come next unless they are skipped.
+    pending: Option<Chunk<'a>>,
+    stack: Vec<ChunkIter<'a>>,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(root: Chunk<'a>) -> Walk<'a> {
+        Walk {
+            root: Some(root),
+            pending: None,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Skips the children of the chunk yielded last, if it is a container.
+    pub fn skip_children(&mut self) {
+        self.pending = None;
+    }
+
+    fn yield_chunk(&mut self, depth: usize, chunk: Chunk<'a>) -> Entry<'a> {
+        // The entry computes the path first, so that the children share it with their parent.
+        let entry = Entry::new(depth, chunk);
+        if entry.is_container() {
+            self.pending = Some(entry.chunk.clone());
+        }
+        entry
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = RiffResult<Entry<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            return Some(Ok(self.yield_chunk(0, root)));
+        }
+        if let Some(container) = self.pending.take() {
+            match container.iter() {
+                Ok(children) => self.stack.push(children),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        while let Some(children) = self.stack.last_mut() {
+            match children.next() {
+                Some(Ok(chunk)) => {
+                    let depth = self.stack.len();
+                    return Some(Ok(self.yield_chunk(depth, chunk)));
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+/// What a walk does after a `Visitor` callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    /// Goes on with the next sibling, without visiting the children of the container.
+    SkipChildren,
+    /// Ends the walk without calling any other callback.
+    Stop,
+}
+
+/// Callbacks for a depth-first walk started by `Chunk::visit`.
+///
+/// `RIFF`, `LIST` and `seqt` chunks are all containers: `enter_container` is called before
+/// their children and `leave_container` after them, even if the children were skipped.
+/// Other chunks are passed to `visit_leaf`.
+pub trait Visitor<'a> {
+    fn enter_container(&mut self, _entry: &Entry<'a>) -> RiffResult<Flow> {
+        Ok(Flow::Continue)
+    }
+
+    fn leave_container(&mut self, _entry: &Entry<'a>) -> RiffResult<Flow> {
+        Ok(Flow::Continue)
+    }
+
+    fn visit_leaf(&mut self, _entry: &Entry<'a>) -> RiffResult<Flow> {
+        Ok(Flow::Continue)
+    }
+}
+
+/// Walks `chunk` at `depth`, returning `Flow::Stop` if the visitor asked to stop.
+pub(crate) fn visit<'a, V>(chunk: Chunk<'a>, depth: usize, visitor: &mut V) -> RiffResult<Flow>
+where
+    V: Visitor<'a> + ?Sized,
+{
+    let entry = Entry::new(depth, chunk);
+    if !entry.is_container() {
+        return visitor.visit_leaf(&entry);
+    }
+    match visitor.enter_container(&entry)? {
+        Flow::Stop => return Ok(Flow::Stop),
+        Flow::SkipChildren => {}
+        Flow::Continue => {
+            for child in entry.chunk.iter()? {
+                if visit(child?, depth + 1, visitor)? == Flow::Stop {
+                    return Ok(Flow::Stop);
+                }
+            }
+        }
+    }
+    visitor.leave_container(&entry)
+}
@@ -0,0 +1,429 @@
+use crate::{
+    error::RiffResult, riff::ChunkIter, writer::ChunkContents, Chunk, ChunkKind, FourCC, RiffError,
+};
+use std::borrow::Cow;
+
+pub const WEBP_ID: &[u8; 4] = b"WEBP";
+pub const VP8_ID: &[u8; 4] = b"VP8 ";
+pub const VP8L_ID: &[u8; 4] = b"VP8L";
+pub const VP8X_ID: &[u8; 4] = b"VP8X";
+pub const ALPH_ID: &[u8; 4] = b"ALPH";
+pub const ICCP_ID: &[u8; 4] = b"ICCP";
+pub const EXIF_ID: &[u8; 4] = b"EXIF";
+pub const XMP_ID: &[u8; 4] = b"XMP ";
+pub const ANIM_ID: &[u8; 4] = b"ANIM";
+pub const ANMF_ID: &[u8; 4] = b"ANMF";
+
+/// Start code following the frame tag of a VP8 key frame.
+const VP8_START_CODE: &[u8] = &[0x9d, 0x01, 0x2a];
+/// Signature byte that begins every VP8L bitstream.
+const VP8L_SIGNATURE: u8 = 0x2f;
+
+fn read_u24(data: &[u8], offset: usize) -> RiffResult<u32> {
+    let bytes = data
+        .get(offset..offset + 3)
+        .ok_or_else(|| RiffError::malformed("payload too short"))?;
+    Ok(u32::from(bytes[0]) | u32::from(bytes[1]) << 8 | u32::from(bytes[2]) << 16)
+}
+
+/// The compression used by a WebP bitstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// A `VP8 ` chunk.
+    Lossy,
+    /// A `VP8L` chunk.
+    Lossless,
+}
+
+/// A `VP8 ` or `VP8L` bitstream along with the dimensions stored in its header.
+/// The pixels themselves are never decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct Bitstream<'a> {
+    pub encoding: Encoding,
+    pub width: u32,
+    pub height: u32,
+    /// The `alpha_is_used` hint of a lossless header. Lossy bitstreams carry their alpha in a
+    /// separate `ALPH` chunk and always report `false`.
+    pub has_alpha: bool,
+    pub data: &'a [u8],
+}
+
+impl<'a> Bitstream<'a> {
+    pub fn from_chunk(chunk: &Chunk<'a>) -> RiffResult<Bitstream<'a>> {
+        let id = chunk.id()?;
+        let data = chunk.content()?;
+        match id.as_bytes() {
+            VP8_ID => Bitstream::from_vp8(data),
+            VP8L_ID => Bitstream::from_vp8l(data),
+            _ => Err(RiffError::malformed("not a VP8 or VP8L chunk")),
+        }
+        .map_err(|err| chunk.locate(err))
+    }
+
+    /// Reads the key frame header of a lossy bitstream.
+    pub fn from_vp8(data: &'a [u8]) -> RiffResult<Bitstream<'a>> {
+        let header = data
+            .get(0..10)
+            .ok_or_else(|| RiffError::malformed("VP8 header too short"))?;
+        let is_key_frame = header[0] & 1 == 0;
+        if !is_key_frame || &header[3..6] != VP8_START_CODE {
+            return Err(RiffError::malformed(
+                "VP8 bitstream does not start with a key frame",
+            ));
+        }
+        let width = u16::from_le_bytes([header[6], header[7]]) & 0x3fff;
+        let height = u16::from_le_bytes([header[8], header[9]]) & 0x3fff;
+        Ok(Bitstream {
+            encoding: Encoding::Lossy,
+            width: u32::from(width),
+            height: u32::from(height),
+            has_alpha: false,
+            data,
+        })
+    }
+
+    /// Reads the image header of a lossless bitstream.
+    pub fn from_vp8l(data: &'a [u8]) -> RiffResult<Bitstream<'a>> {
+        let header = data
+            .get(0..5)
+            .ok_or_else(|| RiffError::malformed("VP8L header too short"))?;
+        if header[0] != VP8L_SIGNATURE {
+            return Err(RiffError::malformed("missing VP8L signature"));
+        }
+        let bits = u32::from_le_bytes([header[1], header[2], header[3], header[4]]);
+        Ok(Bitstream {
+            encoding: Encoding::Lossless,
+            width: (bits & 0x3fff) + 1,
+            height: ((bits >> 14) & 0x3fff) + 1,
+            has_alpha: (bits >> 28) & 1 == 1,
+            data,
+        })
+    }
+}
+
+/// The content of a `VP8X` chunk, present in every extended WebP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vp8x {
+    pub flags: u8,
+    pub canvas_width: u32,
+    pub canvas_height: u32,
+}
+
+impl Vp8x {
+    pub const ICC_PROFILE_FLAG: u8 = 0x20;
+    pub const ALPHA_FLAG: u8 = 0x10;
+    pub const EXIF_FLAG: u8 = 0x08;
+    pub const XMP_FLAG: u8 = 0x04;
+    pub const ANIMATION_FLAG: u8 = 0x02;
+
+    pub fn from_bytes(data: &[u8]) -> RiffResult<Vp8x> {
+        let flags = *data
+            .first()
+            .ok_or_else(|| RiffError::malformed("VP8X payload too short"))?;
+        Ok(Vp8x {
+            flags,
+            canvas_width: read_u24(data, 4)? + 1,
+            canvas_height: read_u24(data, 7)? + 1,
+        })
+    }
+
+    /// The largest canvas width or height, which is stored minus one in 24 bits.
+    pub const MAX_CANVAS_SIZE: u32 = 1 << 24;
+
+    /// Fails unless both sides of the canvas are between 1 and `MAX_CANVAS_SIZE`.
+    pub fn to_bytes(&self) -> RiffResult<[u8; 10]> {
+        let encode = |size: u32| match size {
+            1..=Vp8x::MAX_CANVAS_SIZE => Ok((size - 1).to_le_bytes()),
+            _ => Err(RiffError::malformed(
+                "VP8X canvas size outside of 1 to 2^24",
+            )),
+        };
+        let width = encode(self.canvas_width)?;
+        let height = encode(self.canvas_height)?;
+        Ok([
+            self.flags, 0, 0, 0, width[0], width[1], width[2], height[0], height[1], height[2],
+        ])
+    }
+
+    pub fn has_icc_profile(&self) -> bool {
+        self.flags & Vp8x::ICC_PROFILE_FLAG != 0
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.flags & Vp8x::ALPHA_FLAG != 0
+    }
+
+    pub fn has_exif(&self) -> bool {
+        self.flags & Vp8x::EXIF_FLAG != 0
+    }
+
+    pub fn has_xmp(&self) -> bool {
+        self.flags & Vp8x::XMP_FLAG != 0
+    }
+
+    pub fn has_animation(&self) -> bool {
+        self.flags & Vp8x::ANIMATION_FLAG != 0
+    }
+}
+
+/// The global parameters of an animation, stored in the `ANIM` chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Animation {
+    /// Background color in `[blue, green, red, alpha]` order.
+    pub background_color: [u8; 4],
+    /// Number of times to loop the animation, `0` meaning infinitely.
+    pub loop_count: u16,
+}
+
+impl Animation {
+    pub fn from_bytes(data: &[u8]) -> RiffResult<Animation> {
+        let data = data
+            .get(0..6)
+            .ok_or_else(|| RiffError::malformed("ANIM payload too short"))?;
+        Ok(Animation {
+            background_color: [data[0], data[1], data[2], data[3]],
+            loop_count: u16::from_le_bytes([data[4], data[5]]),
+        })
+    }
+}
+
+/// How a frame is combined with the canvas beneath it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blending {
+    AlphaBlend,
+    NoBlend,
+}
+
+/// What happens to the area covered by a frame once its duration has elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disposal {
+    None,
+    Background,
+}
+
+/// A single `ANMF` frame of an animated WebP.
+#[derive(Debug, Clone)]
+pub struct Frame<'a> {
+    pub x_offset: u32,
+    pub y_offset: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Duration of the frame in milliseconds.
+    pub duration: u32,
+    pub blending: Blending,
+    pub disposal: Disposal,
+    pub alpha: Option<&'a [u8]>,
+    pub bitstream: Bitstream<'a>,
+}
+
+impl<'a> Frame<'a> {
+    pub fn from_chunk(chunk: &Chunk<'a>) -> RiffResult<Frame<'a>> {
+        let header = chunk
+            .content()?
+            .get(0..16)
+            .ok_or_else(|| chunk.locate(RiffError::malformed("ANMF header too short")))?;
+        let mut alpha = None;
+        let mut bitstream = None;
+        for child in ChunkIter::new(chunk, chunk.content_offset() as usize + 16) {
+            let child = child?;
+            match child.id()?.as_bytes() {
+                ALPH_ID => alpha = Some(child.content()?),
+                VP8_ID | VP8L_ID => bitstream = Some(Bitstream::from_chunk(&child)?),
+                _ => {}
+            }
+        }
+        Ok(Frame {
+            x_offset: read_u24(header, 0)? * 2,
+            y_offset: read_u24(header, 3)? * 2,
+            width: read_u24(header, 6)? + 1,
+            height: read_u24(header, 9)? + 1,
+            duration: read_u24(header, 12)?,
+            blending: if header[15] & 0x02 == 0 {
+                Blending::AlphaBlend
+            } else {
+                Blending::NoBlend
+            },
+            disposal: if header[15] & 0x01 == 0 {
+                Disposal::None
+            } else {
+                Disposal::Background
+            },
+            alpha,
+            bitstream: bitstream.ok_or_else(|| {
+                chunk.locate(RiffError::malformed("ANMF frame without a bitstream"))
+            })?,
+        })
+    }
+}
+
+/// A parsed `RIFF WEBP` file. Every slice borrows from the underlying `Chunk`.
+#[derive(Debug, Clone, Default)]
+pub struct WebP<'a> {
+    pub vp8x: Option<Vp8x>,
+    /// The image of a still WebP. Animated files carry their bitstreams in `frames` instead.
+    pub bitstream: Option<Bitstream<'a>>,
+    pub alpha: Option<&'a [u8]>,
+    pub icc_profile: Option<&'a [u8]>,
+    pub exif: Option<&'a [u8]>,
+    pub xmp: Option<&'a [u8]>,
+    pub animation: Option<Animation>,
+    pub frames: Vec<Frame<'a>>,
+}
+
+impl<'a> WebP<'a> {
+    pub fn from_chunk(chunk: &Chunk<'a>) -> RiffResult<WebP<'a>> {
+        chunk.expect_form_type(WEBP_ID)?;
+        let mut result = WebP::default();
+        for child in chunk.iter()? {
+            let child = child?;
+            result.read_child(&child).map_err(|err| child.locate(err))?;
+        }
+        if result.vp8x.is_none() && result.bitstream.is_none() {
+            return Err(chunk.locate(RiffError::malformed("neither a VP8X chunk nor a bitstream")));
+        }
+        Ok(result)
+    }
+
+    fn read_child(&mut self, child: &Chunk<'a>) -> RiffResult<()> {
+        if child.kind() != ChunkKind::Raw {
+            return Ok(());
+        }
+        let content = child.content()?;
+        match child.id()?.as_bytes() {
+            VP8X_ID => self.vp8x = Some(Vp8x::from_bytes(content)?),
+            VP8_ID | VP8L_ID => self.bitstream = Some(Bitstream::from_chunk(child)?),
+            ALPH_ID => self.alpha = Some(content),
+            ICCP_ID => self.icc_profile = Some(content),
+            EXIF_ID => self.exif = Some(content),
+            XMP_ID => self.xmp = Some(content),
+            ANIM_ID => self.animation = Some(Animation::from_bytes(content)?),
+            ANMF_ID => self.frames.push(Frame::from_chunk(child)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether the file uses the extended format, i.e. starts with a `VP8X` chunk.
+    pub fn is_extended(&self) -> bool {
+        self.vp8x.is_some()
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.animation.is_some() || !self.frames.is_empty()
+    }
+
+    /// Whether the image, or the first frame of an animation, is losslessly compressed.
+    pub fn is_lossless(&self) -> bool {
+        self.bitstream
+            .as_ref()
+            .or_else(|| self.frames.first().map(|frame| &frame.bitstream))
+            .is_some_and(|bitstream| bitstream.encoding == Encoding::Lossless)
+    }
+
+    /// The canvas width, taken from `VP8X` if present and from the bitstream otherwise.
+    pub fn width(&self) -> u32 {
+        match (&self.vp8x, &self.bitstream) {
+            (Some(vp8x), _) => vp8x.canvas_width,
+            (None, Some(bitstream)) => bitstream.width,
+            (None, None) => 0,
+        }
+    }
+
+    /// The canvas height, taken from `VP8X` if present and from the bitstream otherwise.
+    pub fn height(&self) -> u32 {
+        match (&self.vp8x, &self.bitstream) {
+            (Some(vp8x), _) => vp8x.canvas_height,
+            (None, Some(bitstream)) => bitstream.height,
+            (None, None) => 0,
+        }
+    }
+}
+
+/// The metadata chunks of a WebP. Setting a field to `None` strips the corresponding chunk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata<'a> {
+    pub icc_profile: Option<&'a [u8]>,
+    pub exif: Option<&'a [u8]>,
+    pub xmp: Option<&'a [u8]>,
+}
+
+impl<'a> WebP<'a> {
+    pub fn metadata(&self) -> Metadata<'a> {
+        Metadata {
+            icc_profile: self.icc_profile,
+            exif: self.exif,
+            xmp: self.xmp,
+        }
+    }
+}
+
+/// Rebuilds the `RIFF WEBP` chunk `chunk` with its `ICCP`, `EXIF` and `XMP ` chunks replaced by
+/// `metadata`. Every other chunk, including the bitstream, is reused as is.
+///
+/// A simple file gaining metadata is upgraded to the extended format, with the canvas size taken
+/// from its bitstream header. The chunks are laid out in the order required by the
+/// specification: `VP8X`, `ICCP`, the image data and any unknown chunks, then `EXIF` and `XMP `.
+pub fn rewrite_metadata<'a>(
+    chunk: &Chunk<'a>,
+    metadata: &Metadata<'a>,
+) -> RiffResult<ChunkContents<'a>> {
+    let webp = WebP::from_chunk(chunk)?;
+    let has_metadata =
+        metadata.icc_profile.is_some() || metadata.exif.is_some() || metadata.xmp.is_some();
+    let vp8x = match (webp.vp8x, &webp.bitstream) {
+        (Some(vp8x), _) => Some(vp8x.flags),
+        (None, Some(bitstream)) if has_metadata => Some(if bitstream.has_alpha {
+            Vp8x::ALPHA_FLAG
+        } else {
+            0
+        }),
+        _ => None,
+    }
+    .map(|flags| {
+        let mut flags = flags & !(Vp8x::ICC_PROFILE_FLAG | Vp8x::EXIF_FLAG | Vp8x::XMP_FLAG);
+        if metadata.icc_profile.is_some() {
+            flags |= Vp8x::ICC_PROFILE_FLAG;
+        }
+        if metadata.exif.is_some() {
+            flags |= Vp8x::EXIF_FLAG;
+        }
+        if metadata.xmp.is_some() {
+            flags |= Vp8x::XMP_FLAG;
+        }
+        Vp8x {
+            flags,
+            canvas_width: webp.width(),
+            canvas_height: webp.height(),
+        }
+    });
+
+    let raw = |id: &[u8], data: Cow<'a, [u8]>| -> RiffResult<ChunkContents<'a>> {
+        Ok(ChunkContents::RawData(FourCC::new(id)?, data))
+    };
+    let mut children = Vec::new();
+    if let Some(vp8x) = vp8x {
+        children.push(raw(VP8X_ID, Cow::Owned(vp8x.to_bytes()?.to_vec()))?);
+    }
+    if let Some(icc_profile) = metadata.icc_profile {
+        children.push(raw(ICCP_ID, Cow::Borrowed(icc_profile))?);
+    }
+    for child in chunk.iter()? {
+        let child = child?;
+        match child.id()?.as_bytes() {
+            VP8X_ID | ICCP_ID | EXIF_ID | XMP_ID => {}
+            _ => children.push(ChunkContents::from_chunk(&child)?),
+        }
+    }
+    if let Some(exif) = metadata.exif {
+        children.push(raw(EXIF_ID, Cow::Borrowed(exif))?);
+    }
+    if let Some(xmp) = metadata.xmp {
+        children.push(raw(XMP_ID, Cow::Borrowed(xmp))?);
+    }
+    Ok(ChunkContents::Children(
+        chunk.id()?,
+        chunk.chunk_type()?,
+        children,
+    ))
+}
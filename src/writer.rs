@@ -0,0 +1,118 @@
+use crate::{error::RiffResult, Chunk, ChunkKind, FourCC};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::Write;
+
+/// An owned description of a chunk that can be written out as RIFF.
+///
+/// Payloads are stored as `Cow` so that a tree built from a parsed `Chunk` keeps borrowing the
+/// original bytes and only the chunks that actually change need to be allocated.
+///
+/// # Example
+///
+/// ```rust
+/// use riffu::{writer::ChunkContents, FourCC};
+/// let riff = ChunkContents::Children(
+///     FourCC::new(b"RIFF").unwrap(),
+///     FourCC::new(b"smpl").unwrap(),
+///     vec![ChunkContents::RawData(
+///         FourCC::new(b"test").unwrap(),
+///         b"hey".to_vec().into(),
+///     )],
+/// );
+/// let bytes = riff.to_bytes().unwrap();
+/// assert_eq!(bytes.len(), 24);
+/// ```
+#[derive(Debug, Clone)]
+pub enum ChunkContents<'a> {
+    RawData(FourCC, Cow<'a, [u8]>),
+    Children(FourCC, FourCC, Vec<ChunkContents<'a>>),
+    ChildrenNoType(FourCC, Vec<ChunkContents<'a>>),
+}
+
+impl<'a> ChunkContents<'a> {
+    /// Copies the structure of `chunk`, borrowing every payload from it.
+    pub fn from_chunk(chunk: &Chunk<'a>) -> RiffResult<ChunkContents<'a>> {
+        let id = chunk.id()?;
+        let result = match chunk.kind() {
+            ChunkKind::Raw => ChunkContents::RawData(id, Cow::Borrowed(chunk.content()?)),
+            ChunkKind::List => ChunkContents::Children(
+                id,
+                chunk.chunk_type()?,
+                chunk
+                    .iter()?
+                    .map(|child| ChunkContents::from_chunk(&child?))
+                    .collect::<RiffResult<_>>()?,
+            ),
+            ChunkKind::Seqt => ChunkContents::ChildrenNoType(
+                id,
+                chunk
+                    .iter()?
+                    .map(|child| ChunkContents::from_chunk(&child?))
+                    .collect::<RiffResult<_>>()?,
+            ),
+        };
+        Ok(result)
+    }
+
+    pub fn id(&self) -> &FourCC {
+        match self {
+            ChunkContents::RawData(id, _) => id,
+            ChunkContents::Children(id, _, _) => id,
+            ChunkContents::ChildrenNoType(id, _) => id,
+        }
+    }
+
+    /// The value of the size field of this chunk, excluding its header and pad byte.
+    pub fn payload_len(&self) -> u64 {
+        match self {
+            ChunkContents::RawData(_, data) => data.len() as u64,
+            ChunkContents::Children(_, _, children) => {
+                4 + children.iter().map(|c| c.total_len()).sum::<u64>()
+            }
+            ChunkContents::ChildrenNoType(_, children) => {
+                children.iter().map(|c| c.total_len()).sum::<u64>()
+            }
+        }
+    }
+
+    /// The number of bytes this chunk occupies once written, including its header and pad byte.
+    pub fn total_len(&self) -> u64 {
+        let payload_len = self.payload_len();
+        8 + payload_len + payload_len % 2
+    }
+
+    /// Writes the chunk and returns the number of bytes written.
+    pub fn write<W>(&self, writer: &mut W) -> RiffResult<u64>
+    where
+        W: Write,
+    {
+        let payload_len = self.payload_len();
+        writer.write_all(self.id().as_bytes())?;
+        writer.write_all(&u32::try_from(payload_len)?.to_le_bytes())?;
+        match self {
+            ChunkContents::RawData(_, data) => writer.write_all(data)?,
+            ChunkContents::Children(_, chunk_type, children) => {
+                writer.write_all(chunk_type.as_bytes())?;
+                for child in children {
+                    child.write(writer)?;
+                }
+            }
+            ChunkContents::ChildrenNoType(_, children) => {
+                for child in children {
+                    child.write(writer)?;
+                }
+            }
+        }
+        if payload_len % 2 == 1 {
+            writer.write_all(&[0])?;
+        }
+        Ok(8 + payload_len + payload_len % 2)
+    }
+
+    pub fn to_bytes(&self) -> RiffResult<Vec<u8>> {
+        let mut result = Vec::with_capacity(self.total_len() as usize);
+        self.write(&mut result)?;
+        Ok(result)
+    }
+}
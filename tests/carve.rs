@@ -0,0 +1,116 @@
+extern crate riffu;
+
+use riffu::carve::{scan, scan_reader, Container, Found};
+use std::io::Read;
+
+/// A reader handing out a few bytes at a time, like a slow pipe.
+struct Trickle<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(self.data.len()).min(7);
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+/// A disk image holding a WAVE and an AVI among noise, ending with a cut SoundFont.
+fn disk_image() -> Vec<u8> {
+    let wave = std::fs::read("test_assets/Chimes.wav").unwrap();
+    let avi = std::fs::read("test_assets/Canimate.avi").unwrap();
+    let sf2 = std::fs::read("test_assets/sample.sf2").unwrap();
+    let mut image = vec![0xaa; 70_000];
+    image.extend_from_slice(b"RIFF\xff\xff\0\0noise");
+    image.extend_from_slice(&wave);
+    image.extend_from_slice(&[0; 13]);
+    image.extend_from_slice(&avi);
+    image.extend_from_slice(b"RIFF");
+    image.extend_from_slice(&sf2[..500]);
+    image
+}
+
+fn summary(found: &[Found]) -> Vec<(u64, Container, [u8; 4], u64, bool)> {
+    found
+        .iter()
+        .map(|f| {
+            let form_type = f.form_type.into_bytes();
+            (f.offset, f.container, form_type, f.len, f.truncated)
+        })
+        .collect()
+}
+
+#[test]
+fn test_scan_slice() {
+    let image = disk_image();
+    let found: Vec<_> = scan(&image).collect();
+    assert_eq!(
+        summary(&found),
+        [
+            (70_013, Container::Riff, *b"WAVE", 15932, false),
+            (85_958, Container::Riff, *b"AVI ", 91960, false),
+            (177_922, Container::Riff, *b"sfbk", 500, true),
+        ]
+    );
+}
+
+#[test]
+fn test_scan_reader_matches_slice() {
+    let image = disk_image();
+    let from_slice: Vec<_> = scan(&image).collect();
+    let from_reader = scan_reader(Trickle { data: &image })
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(summary(&from_reader), summary(&from_slice));
+}
+
+#[test]
+fn test_nested_containers() {
+    let mut inner = b"RIFF\x0e\0\0\0WAVEdata\x02\0\0\0ab".to_vec();
+    let mut outer = b"RIFF\x22\0\0\0smplwrap\x16\0\0\0".to_vec();
+    outer.append(&mut inner);
+    let found: Vec<_> = scan(&outer).collect();
+    assert_eq!(
+        summary(&found),
+        [
+            (0, Container::Riff, *b"smpl", 42, false),
+            (20, Container::Riff, *b"WAVE", 22, false),
+        ]
+    );
+}
+
+#[test]
+fn test_rifx_and_rf64() {
+    let mut image = b"RIFX\0\0\0\x0eWAVEdata\0\0\0\x02ab".to_vec();
+    image.extend_from_slice(b"RF64\xff\xff\xff\xffWAVEds64\x1c\0\0\0");
+    image.extend_from_slice(&50u64.to_le_bytes());
+    image.extend_from_slice(&6u64.to_le_bytes());
+    image.extend_from_slice(&6u64.to_le_bytes());
+    image.extend_from_slice(&0u32.to_le_bytes());
+    image.extend_from_slice(b"data\xff\xff\xff\xffabcdef");
+    let found: Vec<_> = scan(&image).collect();
+    assert_eq!(
+        summary(&found),
+        [
+            (0, Container::Rifx, *b"WAVE", 22, false),
+            (22, Container::Rf64, *b"WAVE", 58, false),
+        ]
+    );
+}
+
+#[test]
+fn test_scan_reader_max_len() {
+    // The sizes claim 4 GiB, which must not be buffered before the candidate is checked.
+    let header = b"RIFF\xff\xff\xff\xffWAVEdata\xf0\xff\xff\xff".as_ref();
+    let reader = header.chain(std::io::repeat(0).take(1 << 20));
+    let found = scan_reader(reader)
+        .with_max_len(4096)
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        summary(&found),
+        [(0, Container::Riff, *b"WAVE", 4096, true)]
+    );
+}
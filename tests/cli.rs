@@ -0,0 +1,184 @@
+#![cfg(feature = "cli")]
+
+extern crate riffu;
+
+mod common;
+
+use common::scratch_copy;
+use riffu::{error::RiffResult, writer::ChunkContents, FourCC};
+use std::process::{Command, Output};
+
+fn riffu(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_riffu"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(args: &[&str]) -> String {
+    let output = riffu(args);
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_tree() {
+    let tree = stdout(&["tree", "test_assets/set_3.riff"]);
+    let lines: Vec<_> = tree.lines().map(|line| line.trim_end()).collect();
+    assert_eq!(lines.len(), 6);
+    assert!(lines[1].starts_with("  LIST:tst1"));
+    assert!(lines[5].ends_with("offset         90  size         10"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout(&["tree", "--json", "test_assets/sample.avi"])).unwrap();
+    let avih = &json["children"][0]["children"][0];
+    assert_eq!(avih["path"], "RIFF:AVI /LIST:hdrl/avih");
+    assert_eq!(avih["offset"], 24);
+    assert_eq!(avih["summary"]["streams"], "2");
+}
+
+#[test]
+fn test_info() -> RiffResult<()> {
+    let mut bext = vec![0; 602];
+    bext[..5].copy_from_slice(b"intro");
+    bext[256..262].copy_from_slice(b"riffu ");
+    bext[346..348].copy_from_slice(&2u16.to_le_bytes());
+    bext.extend_from_slice(b"A=PCM\r\n");
+    let wave = ChunkContents::Children(
+        FourCC::from_array(*b"RIFF"),
+        FourCC::from_array(*b"WAVE"),
+        vec![
+            ChunkContents::RawData(FourCC::from_array(*b"bext"), bext.into()),
+            ChunkContents::RawData(FourCC::from_array(*b"data"), vec![0; 4].into()),
+        ],
+    );
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("bext.wav");
+    std::fs::write(&path, wave.to_bytes()?)?;
+    let info: serde_json::Value =
+        serde_json::from_str(&stdout(&["info", "--json", path.to_str().unwrap()])).unwrap();
+    assert_eq!(info["description"], "Waveform audio");
+    assert_eq!(info["chunks"], 3);
+    let summary = &info["known"][0]["summary"];
+    assert_eq!(summary["description"], "intro");
+    assert_eq!(summary["originator"], "riffu");
+    assert_eq!(summary["version"], "2");
+    assert_eq!(summary["coding_history"], "A=PCM");
+    Ok(())
+}
+
+#[test]
+fn test_dump() {
+    let dump = stdout(&["dump", "test_assets/set_3.riff", "seqt/test"]);
+    assert!(dump.contains("66 69 6e 61 6c 20 74 65 73 74"));
+    assert!(dump.contains("|final test|"));
+    let json: serde_json::Value = serde_json::from_str(&stdout(&[
+        "dump",
+        "--json",
+        "test_assets/set_3.riff",
+        "**/test",
+    ]))
+    .unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+    assert_eq!(json[2]["payload"], "66696e616c2074657374");
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(riffu(&[]).status.code(), Some(2));
+    assert_eq!(riffu(&["frobnicate", "x"]).status.code(), Some(2));
+    assert_eq!(
+        riffu(&["tree", "test_assets/M_busy.ani"]).status.code(),
+        Some(1)
+    );
+    assert!(riffu(&["tree", "--lenient", "test_assets/M_busy.ani"])
+        .status
+        .success());
+}
+
+#[test]
+fn test_extract_and_replace() -> RiffResult<()> {
+    let (_dir, path) = scratch_copy("test_assets/Chimes.wav");
+    let file = path.to_str().unwrap();
+    let fmt = path.with_file_name("fmt.bin");
+    stdout(&["extract", file, "fmt", "-o", fmt.to_str().unwrap()]);
+    assert_eq!(std::fs::read(&fmt)?.len(), 16);
+    assert_eq!(stdout(&["extract", file, "fact"]).len(), 4);
+    assert_eq!(riffu(&["extract", file, "*"]).status.code(), Some(2));
+
+    stdout(&["replace", file, "fact", fmt.to_str().unwrap()]);
+    let edited = riffu::Riff::from_path(&path)?;
+    let fact = edited.as_chunk()?.select_first("fact")?.unwrap();
+    assert_eq!(fact.content()?, &std::fs::read(&fmt)?[..]);
+    assert_eq!(riffu(&["replace", file, "*", file]).status.code(), Some(2));
+    Ok(())
+}
+
+#[test]
+fn test_insert() -> RiffResult<()> {
+    let (_dir, path) = scratch_copy("test_assets/set_3.riff");
+    let file = path.to_str().unwrap();
+    let output = path.with_file_name("out.riff");
+    let output = output.to_str().unwrap();
+    stdout(&[
+        "insert",
+        file,
+        "--into",
+        "LIST:tst1",
+        "--id",
+        "ICMT",
+        "--data",
+        "hi",
+        "--at",
+        "1",
+        "-o",
+        output,
+    ]);
+    // The input is left untouched when another output is given.
+    assert_eq!(
+        std::fs::read(&path)?,
+        std::fs::read("test_assets/set_3.riff")?
+    );
+    let edited = riffu::Riff::from_path(output)?;
+    let children = edited.as_chunk()?.select("LIST:tst1/*")?;
+    assert_eq!(children.len(), 3);
+    assert_eq!(children[1].id()?.as_bytes(), b"ICMT");
+    assert_eq!(children[1].content()?, b"hi");
+
+    let denied = riffu(&["insert", file, "--into", ".", "--id", "LIST", "--data", "x"]);
+    assert_eq!(denied.status.code(), Some(2));
+    let missing = riffu(&["insert", file, "--into", ".", "--id", "ICMT"]);
+    assert_eq!(missing.status.code(), Some(2));
+    Ok(())
+}
+
+#[test]
+fn test_strip() -> RiffResult<()> {
+    let (_dir, path) = scratch_copy("test_assets/sample.avi");
+    let file = path.to_str().unwrap();
+    stdout(&["strip", file, "--id", "JUNK", "--id", "vedt"]);
+    let edited = riffu::Riff::from_path(&path)?;
+    let chunk = edited.as_chunk()?;
+    assert!(chunk.select("**/JUNK")?.is_empty());
+    assert!(chunk.select("**/vedt")?.is_empty());
+    assert!(edited.validate().is_valid());
+    // No temporary file is left behind.
+    assert_eq!(std::fs::read_dir(path.parent().unwrap())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_diff() -> RiffResult<()> {
+    let (_dir, path) = scratch_copy("test_assets/sample.avi");
+    let file = path.to_str().unwrap();
+    stdout(&["strip", file, "--id", "vedt"]);
+    let text = stdout(&["diff", "test_assets/sample.avi", file]);
+    assert_eq!(text, "LIST:hdrl/vedt: removed from offset 312\n");
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout(&["diff", "--json", "test_assets/sample.avi", file])).unwrap();
+    assert_eq!(json[0]["path"], "LIST:hdrl/vedt");
+    assert_eq!(json[0]["kind"], "removed");
+    assert_eq!(json[0]["offset"], 312);
+    assert!(stdout(&["diff", file, file]).is_empty());
+    Ok(())
+}
@@ -0,0 +1,190 @@
+extern crate riffu;
+extern crate riffu_derive;
+
+use riffu::{codec::ChunkCodec, error::RiffResult, writer::ChunkContents, FourCC, Riff, RiffError};
+use riffu_derive::ChunkCodec;
+
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "avih")]
+struct MainHeader {
+    micro_sec_per_frame: u32,
+    max_bytes_per_sec: u32,
+    padding_granularity: u32,
+    flags: u32,
+    total_frames: u32,
+    initial_frames: u32,
+    streams: u32,
+    suggested_buffer_size: u32,
+    width: u32,
+    height: u32,
+    reserved: [u32; 4],
+}
+
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "strh")]
+struct StreamHeader {
+    kind: FourCC,
+    handler: FourCC,
+    flags: u32,
+    priority: u16,
+    language: u16,
+    initial_frames: u32,
+    scale: u32,
+    rate: u32,
+    start: u32,
+    length: u32,
+    suggested_buffer_size: u32,
+    quality: u32,
+    sample_size: u32,
+    frame: [i16; 4],
+}
+
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "test")]
+struct Test<'a> {
+    word: [u8; 3],
+    rest: &'a [u8],
+}
+
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "fmt ")]
+struct Format {
+    format_tag: u16,
+    channels: u16,
+    samples_per_sec: u32,
+    avg_bytes_per_sec: u32,
+    block_align: u16,
+    bits_per_sample: u16,
+    extra: Vec<u8>,
+}
+
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "mark")]
+struct Marker {}
+
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "size")]
+struct Size {
+    len: u32,
+}
+
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "blob")]
+struct Blob<'a> {
+    bytes: &'a [u8],
+}
+
+/// Fields named like the locals of the generated code.
+#[derive(Debug, ChunkCodec)]
+#[chunk(id = "test")]
+struct Shadowing {
+    data: u32,
+    cursor: u16,
+    writer: Vec<u8>,
+}
+
+fn encode<'a, T>(value: &T) -> RiffResult<Vec<u8>>
+where
+    T: ChunkCodec<'a>,
+{
+    let mut payload = Vec::new();
+    value.encode(&mut payload)?;
+    Ok(payload)
+}
+
+#[test]
+fn test_decode_fixed_fields() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let chunk = file.as_chunk()?;
+    let avih = chunk.select_first("LIST:hdrl/avih")?.unwrap();
+    let header = avih.decode::<MainHeader>()?;
+    assert_eq!(header.streams, 2);
+    assert_eq!(encode(&header)?, avih.content()?);
+    // The layouts provided by the crate agree with the derived one.
+    let provided = avih.decode::<riffu::avi::MainHeader>()?;
+    assert_eq!(provided.width, header.width);
+    assert_eq!(encode(&provided)?, avih.content()?);
+    let wave = Riff::from_path("test_assets/Chimes.wav")?;
+    let fmt = wave.as_chunk()?.select_first("fmt")?.unwrap();
+    let format = fmt.decode::<riffu::dls::WaveFormat>()?;
+    assert_eq!(encode(&format)?, fmt.content()?);
+
+    let strh = chunk.select("**/strh")?;
+    let video = strh[0].decode::<StreamHeader>()?;
+    let audio = strh[1].decode::<StreamHeader>()?;
+    assert_eq!(video.kind, FourCC::from_array(*b"vids"));
+    assert_eq!(audio.kind, FourCC::from_array(*b"auds"));
+    assert_eq!(encode(&video)?, strh[0].content()?);
+    Ok(())
+}
+
+#[test]
+fn test_decode_tail() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let test = file.as_chunk()?.select_first("LIST:tst1/test")?.unwrap();
+    let decoded = test.decode::<Test>()?;
+    assert_eq!(&decoded.word, b"hey");
+    assert_eq!(decoded.rest, b" this is a test");
+    assert_eq!(encode(&decoded)?, test.content()?);
+
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    let fmt = file.as_chunk()?.select_first("fmt")?.unwrap();
+    let mut format = fmt.decode::<Format>()?;
+    assert_eq!((format.channels, format.samples_per_sec), (1, 22050));
+    assert!(format.extra.is_empty());
+    format.extra = vec![0, 0];
+    match format.to_contents()? {
+        ChunkContents::RawData(id, payload) => {
+            assert_eq!(id, FourCC::from_array(*b"fmt "));
+            assert_eq!(payload.len(), 18);
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_decode_errors() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let chunk = file.as_chunk()?;
+    let strf = chunk.select_first("**/strf")?.unwrap();
+    assert!(matches!(
+        strf.decode::<MainHeader>(),
+        Err(RiffError::MalformedChunk { .. })
+    ));
+    // A `strh` payload renamed `avih` is too short to hold a main header.
+    let bytes = b"avih\x04\0\0\0abcd";
+    let short = riffu::Chunk::from_bytes(bytes)?;
+    match short.decode::<MainHeader>() {
+        Err(RiffError::MalformedChunk { location, reason }) => {
+            assert_eq!(location.offset, 0);
+            assert_eq!(reason, "payload too short");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_few_fields() -> RiffResult<()> {
+    Marker::decode(b"")?;
+    assert_eq!(encode(&Marker {})?, b"");
+    let size = Size::decode(b"\x10\0\0\0")?;
+    assert_eq!(size.len, 16);
+    assert_eq!(encode(&size)?, b"\x10\0\0\0");
+    let blob = Blob::decode(b"abc")?;
+    assert_eq!(blob.bytes, b"abc");
+    assert_eq!(encode(&blob)?, b"abc");
+    Ok(())
+}
+
+#[test]
+fn test_field_names() -> RiffResult<()> {
+    let bytes = b"\x01\0\0\0\x02\0tail";
+    let decoded = Shadowing::decode(bytes)?;
+    assert_eq!(decoded.data, 1);
+    assert_eq!(decoded.cursor, 2);
+    assert_eq!(decoded.writer, b"tail");
+    assert_eq!(encode(&decoded)?, bytes);
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Copies `asset` to a scratch directory, so each test edits its own copy. The directory is
+/// removed when the returned `TempDir` is dropped.
+pub fn scratch_copy(asset: &str) -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(Path::new(asset).file_name().unwrap());
+    std::fs::copy(asset, &path).unwrap();
+    (dir, path)
+}
@@ -0,0 +1,161 @@
+extern crate riffu;
+
+use riffu::{
+    diff::{diff, Difference, DifferenceKind},
+    error::RiffResult,
+    writer::ChunkContents,
+    Chunk, FourCC, Riff,
+};
+
+fn raw(id: &[u8; 4], payload: &[u8]) -> ChunkContents<'static> {
+    ChunkContents::RawData(FourCC::new(id).unwrap(), payload.to_vec().into())
+}
+
+fn kinds(differences: &[Difference]) -> Vec<(&str, DifferenceKind)> {
+    differences
+        .iter()
+        .map(|difference| (difference.path.as_str(), difference.kind.clone()))
+        .collect()
+}
+
+#[test]
+fn test_identical() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    assert!(diff(&file.as_chunk()?, &file.as_chunk()?)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_typed_fields() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    let old = file.as_chunk()?;
+    let mut fmt = old.select_first("fmt")?.unwrap().content()?.to_vec();
+    fmt[2] = 2;
+    let mut editor = file.edit()?;
+    editor
+        .replace("fmt", raw(b"fmt ", &fmt))?
+        .remove("fact")?
+        .append(
+            ".",
+            ChunkContents::Children(
+                FourCC::new(b"LIST")?,
+                FourCC::new(b"INFO")?,
+                vec![raw(b"INAM", b"a\0")],
+            ),
+        )?;
+    let edited = editor.to_bytes()?;
+    let new = Chunk::from_bytes(&edited)?;
+    assert_eq!(
+        kinds(&diff(&old, &new)?),
+        [
+            (
+                "fmt",
+                DifferenceKind::FieldChanged {
+                    field: "channels",
+                    old: 1,
+                    new: 2
+                }
+            ),
+            ("fact", DifferenceKind::Removed { offset: 36 }),
+            ("LIST:INFO", DifferenceKind::Added { offset: 15920 }),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_typed_fields_and_extension() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    let old = file.as_chunk()?;
+    let mut fmt = old.select_first("fmt")?.unwrap().content()?.to_vec();
+    fmt[2] = 2;
+    // A `cbSize` extension, past the fields of the format.
+    fmt.extend_from_slice(&[0, 0]);
+    let mut editor = file.edit()?;
+    editor.replace("fmt", raw(b"fmt ", &fmt))?;
+    let edited = editor.to_bytes()?;
+    let new = Chunk::from_bytes(&edited)?;
+    assert_eq!(
+        kinds(&diff(&old, &new)?),
+        [
+            ("fmt", DifferenceKind::SizeChanged { old: 16, new: 18 }),
+            (
+                "fmt",
+                DifferenceKind::FieldChanged {
+                    field: "channels",
+                    old: 1,
+                    new: 2
+                }
+            ),
+            ("fmt", DifferenceKind::PayloadChanged { offset: 16 }),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_removed_junk() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let old = file.as_chunk()?;
+    let mut editor = file.edit()?;
+    editor.remove("**/JUNK")?;
+    let edited = editor.to_bytes()?;
+    let differences = diff(&old, &Chunk::from_bytes(&edited)?)?;
+    assert_eq!(differences.len(), old.select("**/JUNK")?.len());
+    for difference in &differences {
+        assert!(matches!(difference.kind, DifferenceKind::Removed { .. }));
+        // Each path designates the removed chunk in the old file.
+        let found = old.select(&difference.path)?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id()?.as_bytes(), b"JUNK");
+    }
+    assert_eq!(differences[0].path, "LIST:hdrl/JUNK");
+    Ok(())
+}
+
+#[test]
+fn test_repeated_and_moved() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let old = file.as_chunk()?;
+    let mut editor = file.edit()?;
+    editor
+        .replace("LIST:hdrl/LIST:strl[1]/strf", raw(b"strf", &[0; 18]))?
+        .move_to("LIST:hdrl/vedt", 0)?;
+    let edited = editor.to_bytes()?;
+    let new = Chunk::from_bytes(&edited)?;
+    let differences = diff(&old, &new)?;
+    assert_eq!(
+        kinds(&differences),
+        [
+            (
+                "LIST:hdrl/LIST:strl[1]/strf",
+                DifferenceKind::SizeChanged { old: 16, new: 18 }
+            ),
+            (
+                "LIST:hdrl/LIST:strl[1]/strf",
+                DifferenceKind::PayloadChanged { offset: 0 }
+            ),
+            ("LIST:hdrl/vedt", DifferenceKind::Moved { from: 3, to: 0 }),
+        ]
+    );
+    assert_eq!(new.select(&differences[0].path)?[0].payload_len()?, 18);
+    assert_eq!(
+        differences[2].to_string(),
+        "LIST:hdrl/vedt: moved from index 3 to 0"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_different_roots() -> RiffResult<()> {
+    let old = Chunk::from_bytes(b"RIFF\x04\0\0\0WAVE")?;
+    let new = Chunk::from_bytes(b"RIFF\x04\0\0\0AVI ")?;
+    assert_eq!(
+        kinds(&diff(&old, &new)?),
+        [
+            (".", DifferenceKind::Removed { offset: 0 }),
+            (".", DifferenceKind::Added { offset: 0 }),
+        ]
+    );
+    Ok(())
+}
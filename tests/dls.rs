@@ -0,0 +1,79 @@
+extern crate riffu;
+
+use riffu::{
+    dls::{ArticulatorLevel, Dls},
+    error::RiffResult,
+    Riff,
+};
+
+#[test]
+fn test_dls_collection() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.dls")?;
+    let dls = Dls::from_chunk(&file.as_chunk()?)?;
+    assert_eq!(dls.name.as_deref(), Some("Test Collection"));
+    assert_eq!(dls.version, Some((0x0001_0002, 0x0003_0004)));
+    assert_eq!(dls.instrument_count, 2);
+    assert_eq!(dls.instruments.len(), 2);
+    assert_eq!(dls.waves.len(), 2);
+    assert_eq!(dls.pool_table.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_dls_instruments() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.dls")?;
+    let dls = Dls::from_chunk(&file.as_chunk()?)?;
+
+    let piano = &dls.instruments[0];
+    assert_eq!(piano.name.as_deref(), Some("Piano"));
+    assert!(!piano.is_drum());
+    assert_eq!(piano.program, 5);
+    assert_eq!(piano.regions.len(), 2);
+    assert_eq!(piano.articulators.len(), 1);
+    assert_eq!(piano.articulators[0].level, ArticulatorLevel::Level1);
+    assert_eq!(piano.articulators[0].connections[0].scale, -1000);
+    let low = &piano.regions[0];
+    assert_eq!(low.key_range, (0, 59));
+    assert_eq!(low.layer, None);
+    let sample = low.sample.as_ref().unwrap();
+    assert_eq!((sample.unity_note, sample.fine_tune), (60, -5));
+    assert_eq!(sample.loops.len(), 1);
+    assert_eq!((sample.loops[0].start, sample.loops[0].length), (10, 20));
+
+    let drums = &dls.instruments[1];
+    assert_eq!(drums.name.as_deref(), Some("Drums"));
+    assert!(drums.is_drum());
+    assert!(drums.articulators.is_empty());
+    let region = &drums.regions[0];
+    assert_eq!(region.key_range, (35, 35));
+    assert_eq!(region.layer, Some(2));
+    assert_eq!(region.articulators[0].level, ArticulatorLevel::Level2);
+    assert_eq!(region.articulators[0].connections.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_dls_wave_links() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.dls")?;
+    let dls = Dls::from_chunk(&file.as_chunk()?)?;
+    let piano = &dls.instruments[0];
+
+    let wave = dls.wave(&piano.regions[0]).unwrap();
+    assert_eq!(wave.name.as_deref(), Some("Wave B"));
+    assert_eq!(wave.data, b"\x05\x00\x06\x00");
+    assert_eq!(wave.sample.as_ref().unwrap().unity_note, 64);
+
+    let wave = dls.wave(&piano.regions[1]).unwrap();
+    assert_eq!(wave.name.as_deref(), Some("Wave A"));
+    assert_eq!(wave.format.samples_per_sec, 22050);
+    assert_eq!(wave.format.bits_per_sample, 16);
+    assert_eq!(wave.data.len(), 8);
+    Ok(())
+}
+
+#[test]
+fn test_non_dls_form_type() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.sf2")?;
+    assert!(Dls::from_chunk(&file.as_chunk()?).is_err());
+    Ok(())
+}
@@ -1,3 +1,9 @@
+#![allow(
+    clippy::iter_skip_next,
+    clippy::assertions_on_constants,
+    clippy::useless_vec
+)]
+
 extern crate riffu;
 
 use riffu::{error::RiffResult, Riff};
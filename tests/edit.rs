@@ -0,0 +1,152 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult, validate::validate, writer::ChunkContents, Chunk, FourCC, Riff, RiffError,
+};
+
+fn raw(id: &[u8; 4], payload: &[u8]) -> ChunkContents<'static> {
+    ChunkContents::RawData(FourCC::new(id).unwrap(), payload.to_vec().into())
+}
+
+fn ids(chunk: &Chunk<'_>, query: &str) -> RiffResult<Vec<[u8; 4]>> {
+    chunk
+        .select(query)?
+        .iter()
+        .map(|chunk| Ok(chunk.id()?.into_bytes()))
+        .collect()
+}
+
+#[test]
+fn test_remove_junk() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let mut editor = file.edit()?;
+    editor.remove("**/JUNK")?;
+    let mut edited = Vec::new();
+    let written = editor.write(&mut edited)?;
+    assert_eq!(written as usize, edited.len());
+    let junk_len: usize = file
+        .as_chunk()?
+        .select("**/JUNK")?
+        .iter()
+        .map(|junk| junk.as_bytes().len())
+        .sum();
+    assert_eq!(edited.len(), 2010588 - junk_len);
+    assert!(validate(&edited).is_valid());
+
+    let chunk = Chunk::from_bytes(&edited)?;
+    assert!(chunk.select("**/JUNK")?.is_empty());
+    let idx1 = chunk.select_first("idx1")?.unwrap();
+    let original = file.as_chunk()?.select_first("idx1")?.unwrap();
+    assert_eq!(idx1.as_bytes(), original.as_bytes());
+    Ok(())
+}
+
+#[test]
+fn test_replace_and_append() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    let mut editor = file.edit()?;
+    let info = ChunkContents::Children(
+        FourCC::new(b"LIST")?,
+        FourCC::new(b"INFO")?,
+        vec![raw(b"INAM", b"Chimes\0")],
+    );
+    let fmt = raw(
+        b"fmt ",
+        &[
+            1, 0, 1, 0, 0x22, 0x56, 0, 0, 0x22, 0x56, 0, 0, 1, 0, 8, 0, 0, 0,
+        ],
+    );
+    editor.replace("fmt", fmt)?.append(".", info)?;
+    let edited = editor.to_bytes()?;
+    assert!(validate(&edited).is_valid());
+
+    let chunk = Chunk::from_bytes(&edited)?;
+    assert_eq!(ids(&chunk, "*")?, [*b"fmt ", *b"fact", *b"data", *b"LIST"]);
+    assert_eq!(chunk.select_first("fmt")?.unwrap().payload_len()?, 18);
+    let name = chunk.select_first("LIST:INFO/INAM")?.unwrap();
+    assert_eq!(name.content()?, b"Chimes\0");
+    assert_eq!(chunk.payload_len()?, 15924 + 2 + 28);
+    Ok(())
+}
+
+#[test]
+fn test_insert_into_info() -> RiffResult<()> {
+    let bytes = b"RIFF\x26\0\0\0WAVELIST\x1a\0\0\0INFOINAM\x02\0\0\0abICMT\x04\0\0\0note";
+    let chunk = Chunk::from_bytes(bytes)?;
+    let mut editor = riffu::edit::Editor::new(chunk);
+    editor
+        .insert("LIST:INFO", 1, raw(b"IART", b"someone"))?
+        .insert("LIST:INFO", 0, raw(b"ISFT", b"riffu"))?;
+    let edited = editor.to_bytes()?;
+    let chunk = Chunk::from_bytes(&edited)?;
+    assert_eq!(
+        ids(&chunk, "LIST:INFO/*")?,
+        [*b"ISFT", *b"INAM", *b"IART", *b"ICMT"]
+    );
+    assert!(validate(&edited).is_valid());
+    Ok(())
+}
+
+#[test]
+fn test_move() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let mut editor = file.edit()?;
+    editor.move_to("seqt", 0)?;
+    editor.move_to("LIST:tst1/test[0]", 5)?;
+    let edited = editor.to_bytes()?;
+    assert_eq!(edited.len(), 108);
+    let chunk = Chunk::from_bytes(&edited)?;
+    assert_eq!(ids(&chunk, "*")?, [*b"seqt", *b"LIST"]);
+    let tests = chunk.select("LIST:tst1/test")?;
+    assert_eq!(tests[0].content()?, b"hey this is another test");
+    assert_eq!(tests[1].content()?, b"hey this is a test");
+    Ok(())
+}
+
+#[test]
+fn test_invalid_edits() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let mut editor = file.edit()?;
+    assert!(matches!(
+        editor.replace("LIST:none", raw(b"test", b"")),
+        Err(RiffError::InvalidQuery { .. })
+    ));
+    assert!(matches!(
+        editor.append("LIST:tst1/test", raw(b"test", b"")),
+        Err(RiffError::MalformedChunk { .. })
+    ));
+    assert!(editor.append("**", raw(b"test", b"")).is_err());
+    // Removing nothing is fine, since stripping chunks that are absent is a common need.
+    editor.remove("**/JUNK")?;
+    assert_eq!(editor.to_bytes()?, std::fs::read("test_assets/set_3.riff")?);
+    Ok(())
+}
+
+#[test]
+fn test_lost_edits_are_rejected() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let mut editor = file.edit()?;
+    editor.replace("LIST:tst1", raw(b"JUNK", b""))?;
+    for result in [
+        editor.append("LIST:tst1", raw(b"test", b"")).map(drop),
+        editor.insert("LIST:tst1", 0, raw(b"test", b"")).map(drop),
+        editor.move_to("LIST:tst1/test[1]", 0).map(drop),
+    ] {
+        assert!(matches!(result, Err(RiffError::MalformedChunk { .. })));
+    }
+
+    let mut editor = file.edit()?;
+    editor.append("seqt", raw(b"test", b""))?;
+    editor.move_to("LIST:tst1/test[1]", 0)?;
+    assert!(editor.remove("seqt").is_err());
+    assert!(editor.replace("LIST:tst1", raw(b"JUNK", b"")).is_err());
+    // Chunks next to the pending edits can still go.
+    editor.remove("seqt/test")?;
+    assert!(editor.remove("LIST:tst1/test[1]").is_err());
+    editor.remove("LIST:tst1/test[0]")?;
+    let edited = editor.to_bytes()?;
+    let chunk = Chunk::from_bytes(&edited)?;
+    assert_eq!(chunk.select("LIST:tst1/test")?.len(), 1);
+    assert_eq!(chunk.select("seqt/test")?.len(), 1);
+    Ok(())
+}
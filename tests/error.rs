@@ -0,0 +1,86 @@
+extern crate riffu;
+
+use riffu::{error::RiffResult, webp::WebP, Chunk, Riff, RiffError};
+
+/// `RIFF smpl` holding `LIST tst1`, whose second child claims more bytes than remain.
+fn overrunning_child() -> Vec<u8> {
+    let mut list = b"LIST\x1a\0\0\0tst1".to_vec();
+    list.extend_from_slice(b"good\x02\0\0\0ab");
+    list.extend_from_slice(b"bad \x40\0\0\0abcd");
+    let mut riff = b"RIFF".to_vec();
+    riff.extend_from_slice(&(4 + list.len() as u32).to_le_bytes());
+    riff.extend_from_slice(b"smpl");
+    riff.extend_from_slice(&list);
+    riff
+}
+
+#[test]
+fn test_error_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync + std::error::Error + 'static>() {}
+    assert_send_sync::<RiffError>();
+}
+
+#[test]
+fn test_payload_overruns_parent() -> RiffResult<()> {
+    let bytes = overrunning_child();
+    let chunk = Chunk::from_bytes(&bytes)?;
+    let list = chunk.iter()?.next().unwrap()?;
+    let mut children = list.iter()?;
+    assert!(children.next().unwrap().is_ok());
+    match children.next().unwrap() {
+        Err(RiffError::PayloadOverrunsParent {
+            location,
+            payload_len,
+            available,
+        }) => {
+            assert_eq!(location.offset, 34);
+            assert_eq!(location.path, "RIFF:smpl/LIST:tst1/bad ");
+            assert_eq!(payload_len, 64);
+            assert_eq!(available, 4);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(children.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_truncated_header() {
+    match Chunk::from_bytes(b"RIFF\x04\0") {
+        Err(RiffError::TruncatedHeader {
+            location,
+            needed,
+            available,
+        }) => {
+            assert_eq!(location.offset, 0);
+            assert_eq!((needed, available), (8, 6));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_unexpected_container_type() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    let err = WebP::from_chunk(&file.as_chunk()?).unwrap_err();
+    match &err {
+        RiffError::UnexpectedContainerType { location, found } => {
+            assert_eq!(found.as_bytes(), b"WAVE");
+            assert_eq!(location.path, "RIFF:WAVE");
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(
+        err.to_string(),
+        "unexpected container type \"WAVE\" at offset 0 (RIFF:WAVE)"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_io_error_source() {
+    let err = Riff::from_path("test_assets/does_not_exist.riff").unwrap_err();
+    assert!(matches!(err, RiffError::Io(_)));
+    assert!(std::error::Error::source(&err).is_some());
+    assert!(err.location().is_none());
+}
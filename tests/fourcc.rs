@@ -0,0 +1,59 @@
+extern crate riffu;
+
+use riffu::{error::RiffResult, FourCC, Riff, RiffError};
+use std::collections::HashSet;
+
+const LIST: FourCC = FourCC::from_array(*b"LIST");
+const JUNK: FourCC = FourCC::from_array(*b"JUNK");
+
+#[test]
+fn test_match_on_constants() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let hdrl = file.as_chunk()?.select_first("LIST:hdrl")?.unwrap();
+    let mut lists = 0;
+    let mut junk = 0;
+    for child in hdrl.iter()? {
+        match child?.id()? {
+            LIST => lists += 1,
+            JUNK => junk += 1,
+            _ => {}
+        }
+    }
+    assert_eq!((lists, junk), (2, 1));
+    Ok(())
+}
+
+#[test]
+fn test_validation() {
+    assert!(FourCC::new(b"\0\x01\xfe\xff").is_ok());
+    assert!(matches!(
+        FourCC::new_checked(b"ab\xffd"),
+        Err(RiffError::InvalidFourCC { .. })
+    ));
+    assert!(FourCC::new_checked(b"abc").is_err());
+    assert!(FourCC::new_checked(b"fmt ").unwrap().is_printable());
+    assert!(!FourCC::new_unchecked(*b"\0\0\0\0").is_printable());
+}
+
+#[test]
+fn test_padding() -> RiffResult<()> {
+    assert_eq!(FourCC::padded("fmt")?.as_bytes(), b"fmt ");
+    assert_eq!("a".parse::<FourCC>()?.as_bytes(), b"a   ");
+    assert_eq!("data".parse::<FourCC>()?.as_bytes(), b"data");
+    assert!("".parse::<FourCC>().is_err());
+    assert!("toolong".parse::<FourCC>().is_err());
+    assert!("é".parse::<FourCC>().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_traits() -> RiffResult<()> {
+    let mut ids = vec![FourCC::padded("fmt")?, LIST, JUNK, LIST];
+    ids.sort();
+    assert_eq!(ids, [JUNK, LIST, LIST, FourCC::padded("fmt")?]);
+    let unique: HashSet<FourCC> = ids.iter().copied().collect();
+    assert_eq!(unique.len(), 3);
+    assert_eq!(LIST.to_string(), "LIST");
+    assert_eq!(FourCC::new(b"a\\\t\x80")?.to_string(), "a\\x5c\\x09\\x80");
+    Ok(())
+}
@@ -0,0 +1,74 @@
+extern crate riffu;
+
+mod common;
+
+use common::scratch_copy;
+use riffu::{error::RiffResult, Riff, RiffError, RiffMut};
+
+#[test]
+fn test_overwrite() -> RiffResult<()> {
+    let (_dir, path) = scratch_copy("test_assets/set_3.riff");
+    let mut file = RiffMut::from_path(&path)?;
+    let offset = file
+        .as_chunk()?
+        .select_first("LIST:tst1/test")?
+        .unwrap()
+        .offset();
+    file.overwrite(offset, b"HEY THIS IS A TEST")?;
+    assert!(matches!(
+        file.overwrite(offset, b"too short"),
+        Err(RiffError::MalformedChunk { .. })
+    ));
+    file.flush()?;
+    drop(file);
+
+    let file = Riff::from_path(&path)?;
+    let test = file.as_chunk()?.select_first("LIST:tst1/test")?.unwrap();
+    assert_eq!(test.content()?, b"HEY THIS IS A TEST");
+    Ok(())
+}
+
+#[test]
+fn test_convert_to_junk() -> RiffResult<()> {
+    let (_dir, path) = scratch_copy("test_assets/sample.avi");
+    let mut file = RiffMut::from_path(&path)?;
+    let offset = file
+        .as_chunk()?
+        .select_first("LIST:hdrl/vedt")?
+        .unwrap()
+        .offset();
+    file.convert_to_junk(offset)?;
+    assert!(file.convert_to_junk(0).is_err());
+    assert!(file.convert_to_junk(offset + 1).is_err());
+    let chunk = file.as_chunk()?;
+    assert!(chunk.select("LIST:hdrl/vedt")?.is_empty());
+    let junk = chunk.select("LIST:hdrl/JUNK")?;
+    assert_eq!(junk.len(), 2);
+    assert_eq!(junk[0].offset(), offset);
+    assert_eq!(junk[0].content()?, [0; 8]);
+    drop(file);
+    Ok(())
+}
+
+#[test]
+fn test_shrink() -> RiffResult<()> {
+    let (_dir, path) = scratch_copy("test_assets/set_3.riff");
+    let original = std::fs::read(&path)?;
+    let mut file = RiffMut::from_path(&path)?;
+    let offset = file.as_chunk()?.select("LIST:tst1/test")?[1].offset();
+    // "hey this is another test" holds 24 bytes, leaving 18 for `JUNK` with its header.
+    file.shrink(offset, b"short")?;
+    assert!(file.shrink(offset, b"a").is_err());
+    let chunk = file.as_chunk()?;
+    let children = chunk.select("LIST:tst1/*")?;
+    assert_eq!(children.len(), 3);
+    assert_eq!(children[1].content()?, b"short");
+    assert_eq!(children[2].id()?.as_bytes(), b"JUNK");
+    assert_eq!(children[2].payload_len()?, 10);
+    assert_eq!(chunk.as_bytes().len(), original.len());
+    // Everything after the shrunk chunk stays in place.
+    let seqt = chunk.select_first("seqt")?.unwrap();
+    assert_eq!(seqt.as_bytes(), &original[82..]);
+    drop(file);
+    Ok(())
+}
@@ -0,0 +1,63 @@
+extern crate riffu;
+
+use riffu::{error::RiffResult, ChunkIter, Riff};
+
+#[test]
+fn test_offsets_and_parents() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let root = file.as_chunk()?;
+    assert_eq!(root.offset(), 0);
+    assert!(root.parent().is_none());
+    assert!(root.siblings()?.is_empty());
+
+    let strf = root.select_first("LIST:hdrl/LIST:strl[1]/strf")?.unwrap();
+    assert_eq!(strf.offset(), 288);
+    assert_eq!(strf.path(), "RIFF:AVI /LIST:hdrl/LIST:strl/strf");
+    let strl = strf.parent().unwrap();
+    assert_eq!(strl.offset(), 212);
+    assert_eq!(strl.chunk_type()?.as_bytes(), b"strl");
+    let hdrl = strl.parent().unwrap();
+    assert_eq!(hdrl.offset(), 12);
+    assert_eq!(hdrl.parent().unwrap().offset(), 0);
+    assert!(hdrl.parent().unwrap().parent().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_siblings() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let root = file.as_chunk()?;
+    let strf = root.select_first("LIST:hdrl/LIST:strl[1]/strf")?.unwrap();
+    let siblings = strf.siblings()?;
+    assert_eq!(siblings.len(), 1);
+    assert_eq!(siblings[0].id()?.as_bytes(), b"strh");
+    assert_eq!(siblings[0].offset(), 224);
+
+    let avih = root.select_first("LIST:hdrl/avih")?.unwrap();
+    let offsets: Vec<_> = avih.siblings()?.iter().map(|c| c.offset()).collect();
+    assert_eq!(offsets, [88, 212, 312, 328]);
+
+    // Chunks of a bare sequence do not know about each other.
+    let sequence = ChunkIter::from_bytes(b"abcd\x02\0\0\0xyefgh\0\0\0\0");
+    for chunk in sequence {
+        assert!(chunk?.siblings()?.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_offsets_of_iterated_chunks() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let root = file.as_chunk()?;
+    let offsets: Vec<_> = root
+        .iter()?
+        .map(|chunk| chunk.map(|chunk| chunk.offset()))
+        .collect::<RiffResult<_>>()?;
+    assert_eq!(offsets, [12, 82]);
+    let seqt = root.iter()?.nth(1).unwrap()?;
+    let test = seqt.iter()?.next().unwrap()?;
+    assert_eq!(test.offset(), 90);
+    assert_eq!(test.parent().unwrap().offset(), 82);
+    assert!(test.siblings()?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,172 @@
+extern crate riffu;
+
+use riffu::{
+    error::{Location, RiffResult},
+    options::{Warning, WarningKind},
+    Chunk, ChunkIter, ChunkKind, FourCC, ParseOptions, Riff, RiffError,
+};
+
+#[test]
+fn test_strict_rejects_oversized_riff() {
+    // The `RIFF` size of this cursor claims 8 more bytes than the file holds.
+    let file = Riff::from_path("test_assets/M_busy.ani").unwrap();
+    assert!(matches!(
+        file.as_chunk(),
+        Err(RiffError::PayloadOverrunsParent { .. })
+    ));
+}
+
+#[test]
+fn test_lenient_clamps_oversized_riff() -> RiffResult<()> {
+    let file = Riff::from_path_with_options("test_assets/M_busy.ani", ParseOptions::lenient())?;
+    let chunk = file.as_chunk()?;
+    assert_eq!(chunk.payload_len()?, 18906);
+    assert_eq!(chunk.iter()?.count(), 5);
+    assert!(chunk.iter()?.all(|child| child.is_ok()));
+    assert_eq!(
+        chunk.warnings(),
+        [Warning {
+            location: Location::new(0, "RIFF:ACON".to_string()),
+            kind: WarningKind::SizeClamped {
+                declared: 18914,
+                available: 18906,
+            },
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_trailing_bytes_after_root() -> RiffResult<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("trailing.wav");
+    let mut bytes = std::fs::read("test_assets/Chimes.wav")?;
+    bytes.extend_from_slice(b"xx");
+    std::fs::write(&path, &bytes)?;
+
+    let file = Riff::from_path(&path)?;
+    let chunk = file.as_chunk()?;
+    assert_eq!(chunk.payload_len()?, 15924);
+    assert_eq!(
+        chunk.warnings(),
+        [Warning {
+            location: Location::new(15932, String::new()),
+            kind: WarningKind::TrailingBytes { len: 2 },
+        }]
+    );
+
+    let file = Riff::from_path_with_options(&path, ParseOptions::strict().reject_trailing())?;
+    assert!(matches!(
+        file.as_chunk(),
+        Err(RiffError::MalformedChunk { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_missing_padding() -> RiffResult<()> {
+    let bytes = b"RIFF\x0f\0\0\0smplodd \x03\0\0\0abc\0";
+    assert!(Chunk::from_bytes(bytes)?.iter()?.next().unwrap().is_err());
+    let chunk = Chunk::from_bytes_with_options(bytes, ParseOptions::lenient())?;
+    let child = chunk.iter()?.next().unwrap()?;
+    assert_eq!(child.content()?, b"abc");
+    let warnings = chunk.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::MissingPadding);
+    assert_eq!(warnings[0].location.path, "RIFF:smpl/odd ");
+    Ok(())
+}
+
+#[test]
+fn test_short_even_chunk_is_clamped() -> RiffResult<()> {
+    // Both chunks have an even size and end one byte early, which is not a missing pad byte.
+    let bytes = b"LIST\x0e\0\0\0tst1abcd\x02\0\0\0x";
+    let list = ChunkIter::from_bytes_with_options(bytes, ParseOptions::lenient())
+        .next()
+        .unwrap()?;
+    assert_eq!(list.payload_len()?, 13);
+    let child = list.iter()?.next().unwrap()?;
+    assert_eq!(child.content()?, b"x");
+    let kinds: Vec<_> = list.warnings().into_iter().map(|w| w.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            WarningKind::SizeClamped {
+                declared: 14,
+                available: 13,
+            },
+            WarningKind::SizeClamped {
+                declared: 2,
+                available: 1,
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_trailing_junk() -> RiffResult<()> {
+    let bytes = b"test\x02\0\0\0ab\0\0\xff\xfe\x01\x02\x03\x04\x05\x06";
+    let strict: Vec<_> = ChunkIter::from_bytes(bytes).collect();
+    assert_eq!(strict.len(), 2);
+    assert!(strict[1].is_err());
+
+    let mut lenient = ChunkIter::from_bytes_with_options(bytes, ParseOptions::lenient());
+    assert_eq!(lenient.next().unwrap()?.content()?, b"ab");
+    assert!(lenient.next().is_none());
+    assert_eq!(
+        lenient.warnings()[0].kind,
+        WarningKind::TrailingBytes { len: 10 }
+    );
+    assert_eq!(lenient.warnings()[0].location.offset, 10);
+    Ok(())
+}
+
+#[test]
+fn test_warnings_are_not_repeated() -> RiffResult<()> {
+    let bytes = b"RIFF\x0f\0\0\0smplodd \x03\0\0\0abc\0";
+    let chunk = Chunk::from_bytes_with_options(bytes, ParseOptions::lenient())?;
+    for _ in 0..3 {
+        chunk.iter()?.for_each(drop);
+    }
+    assert_eq!(chunk.warnings().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_custom_containers() -> RiffResult<()> {
+    let bytes =
+        b"RIFF\x2c\0\0\0abcdGRP \x0e\0\0\0grp1test\x02\0\0\0abDMSG\x0a\0\0\0test\x02\0\0\0cd";
+    let group = FourCC::from_array(*b"GRP ");
+    let sequence = FourCC::from_array(*b"DMSG");
+    let chunk = Chunk::from_bytes(bytes)?;
+    assert_eq!(chunk.walk().count(), 3);
+
+    let options = ParseOptions::strict()
+        .with_list(group, None)
+        .with_sequence(sequence, Some(FourCC::from_array(*b"abcd")));
+    let chunk = Chunk::from_bytes_with_options(bytes, options)?;
+    let kinds: Vec<_> = chunk
+        .walk()
+        .map(|entry| entry.map(|entry| (entry.depth, entry.chunk.kind())))
+        .collect::<RiffResult<_>>()?;
+    assert_eq!(
+        kinds,
+        [
+            (0, ChunkKind::List),
+            (1, ChunkKind::List),
+            (2, ChunkKind::Raw),
+            (1, ChunkKind::Seqt),
+            (2, ChunkKind::Raw),
+        ]
+    );
+    assert_eq!(chunk.select("GRP :grp1/test")?[0].content()?, b"ab");
+    assert_eq!(chunk.select("DMSG/test")?[0].content()?, b"cd");
+
+    // Restricted to another form type, `DMSG` stays opaque.
+    let options =
+        ParseOptions::strict().with_sequence(sequence, Some(FourCC::from_array(*b"WAVE")));
+    let chunk = Chunk::from_bytes_with_options(bytes, options)?;
+    assert_eq!(chunk.select_first("DMSG")?.unwrap().kind(), ChunkKind::Raw);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+extern crate riffu;
+
+use riffu::{error::RiffResult, query::Query, Riff, RiffError};
+
+fn ids(chunks: &[riffu::Chunk<'_>]) -> RiffResult<Vec<[u8; 4]>> {
+    chunks
+        .iter()
+        .map(|chunk| Ok(chunk.id()?.into_bytes()))
+        .collect()
+}
+
+#[test]
+fn test_select_stream_headers() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let chunk = file.as_chunk()?;
+    let avih = chunk.select("LIST:hdrl/avih")?;
+    assert_eq!(avih.len(), 1);
+    assert_eq!(avih[0].payload_len()?, 56);
+
+    let strf = chunk.select("LIST:hdrl/LIST:strl[1]/strf")?;
+    assert_eq!(strf.len(), 1);
+    assert_eq!(strf[0].payload_len()?, 16);
+
+    let strh = chunk.select("LIST:hdrl/LIST:strl/strh")?;
+    assert_eq!(strh.len(), 2);
+    assert!(chunk.select("LIST:hdrl/LIST:strl[2]/strh")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_select_wildcards() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let chunk = file.as_chunk()?;
+    assert_eq!(ids(&chunk.select("*")?)?, [*b"LIST", *b"LIST", *b"idx1"]);
+    assert_eq!(
+        ids(&chunk.select("LIST:hdrl/*")?)?,
+        [*b"avih", *b"LIST", *b"LIST", *b"vedt", *b"JUNK"]
+    );
+    assert_eq!(chunk.select("*:strl")?.len(), 0);
+    assert_eq!(chunk.select("*/*:strl/*")?.len(), 4);
+    assert_eq!(chunk.select("LIST:*")?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_select_recursive() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let chunk = file.as_chunk()?;
+    let strf = chunk.select("**/strf")?;
+    assert_eq!(strf.len(), 2);
+    assert!(strf[0].payload_len()? == 40 && strf[1].payload_len()? == 16);
+    assert_eq!(chunk.select("**/LIST:strl/**/strh")?.len(), 2);
+    // Both ways of matching `**` twice reach each chunk, which is returned once.
+    assert_eq!(chunk.select("**/**/strh")?.len(), 2);
+    assert_eq!(chunk.select_first("**/strh")?.unwrap().payload_len()?, 56);
+    assert_eq!(chunk.select(".")?[0].offset(), 0);
+    assert_eq!(chunk.select("./LIST:hdrl/./avih")?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_padded_identifiers() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    let chunk = file.as_chunk()?;
+    assert_eq!(chunk.select("fmt")?.len(), 1);
+    assert_eq!(chunk.select("fmt ")?.len(), 1);
+    assert!(chunk.select_first("smpl")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_invalid_queries() {
+    for query in &["", "LIST//strf", "LIST[1", "LIST[a]", "toolong", "LIST:"] {
+        match query.parse::<Query>() {
+            Err(RiffError::InvalidQuery { query: found, .. }) => assert_eq!(&found, query),
+            other => panic!("{:?} parsed as {:?}", query, other),
+        }
+    }
+}
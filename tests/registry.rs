@@ -0,0 +1,118 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult,
+    options::ParseOptions,
+    registry::{ChunkInfo, Registry},
+    validate::{validate_with_options, validate_with_registry, IssueKind},
+    Chunk, ChunkKind, FourCC, Riff,
+};
+
+fn id(value: &str) -> FourCC {
+    value.parse().unwrap()
+}
+
+#[test]
+fn test_known_chunks() {
+    let registry = Registry::new();
+    for form_type in &["WAVE", "AVI", "ACON", "PAL", "RMID", "WEBP", "sfbk", "DLS"] {
+        assert!(registry.form_type(id(form_type)).is_some());
+    }
+    assert!(registry.form_type(id("xxxx")).is_none());
+
+    let strl = registry.lookup(Some(id("AVI")), id("strl")).unwrap();
+    assert_eq!(strl.kind, ChunkKind::List);
+    assert!(!strl.singleton);
+    // The same identifier means different things depending on the form type.
+    let pal = registry.lookup(Some(id("PAL")), id("data")).unwrap();
+    let rmid = registry.lookup(Some(id("RMID")), id("data")).unwrap();
+    assert_ne!(pal.description, rmid.description);
+    // Chunks of other form types are not considered for a known form type.
+    assert!(registry.lookup(Some(id("WAVE")), id("avih")).is_none());
+    assert!(registry.lookup(Some(id("xxxx")), id("avih")).is_some());
+    assert!(
+        registry
+            .lookup(Some(id("WEBP")), id("INAM"))
+            .unwrap()
+            .singleton
+    );
+}
+
+#[test]
+fn test_lookup_chunk() -> RiffResult<()> {
+    let registry = Registry::new();
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let root = file.as_chunk()?;
+    assert!(registry.lookup_chunk(&root).is_none());
+    let hdrl = root.select_first("LIST:hdrl")?.unwrap();
+    assert_eq!(registry.lookup_chunk(&hdrl).unwrap().description, "Header");
+    let strf = root.select_first("**/strf")?.unwrap();
+    assert_eq!(
+        registry.lookup_chunk(&strf).unwrap().description,
+        "Stream format"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_extend() {
+    let bytes = b"RIFF\x18\0\0\0abcdvndr\x02\0\0\0abvndr\x02\0\0\0ab";
+    let mut registry = Registry::empty();
+    let report = validate_with_registry(bytes, &registry);
+    assert_eq!(
+        report.issues[0].kind,
+        IssueKind::UnknownFormType { found: *b"abcd" }
+    );
+    let info = ChunkInfo {
+        description: format!("Vendor {}", 1).into(),
+        kind: ChunkKind::Raw,
+        singleton: true,
+    };
+    registry
+        .register_form_type(id("abcd"), "Vendor format")
+        .register(Some(id("abcd")), id("vndr"), info);
+    let report = validate_with_registry(bytes, &registry);
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(
+        report.issues[0].kind,
+        IssueKind::DuplicateSingleton { id: *b"vndr" }
+    );
+    assert_eq!(report.issues[0].location.offset, 22);
+}
+
+#[test]
+fn test_validate_custom_containers() {
+    let bytes = b"RIFF\x20\0\0\0abcdgrp \x14\0\0\0vndr\x02\0\0\0abvndr\x02\0\0\0ab";
+    let mut registry = Registry::new();
+    let info = ChunkInfo {
+        description: "Vendor settings".into(),
+        kind: ChunkKind::Raw,
+        singleton: true,
+    };
+    registry
+        .register_form_type(id("abcd"), "Vendor format")
+        .register(Some(id("abcd")), id("vndr"), info);
+    assert!(validate_with_registry(bytes, &registry).issues.is_empty());
+    let options = ParseOptions::strict().with_sequence(id("grp"), Some(id("abcd")));
+    let report = validate_with_options(bytes, &registry, options);
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(
+        report.issues[0].kind,
+        IssueKind::DuplicateSingleton { id: *b"vndr" }
+    );
+    assert_eq!(report.issues[0].location.offset, 30);
+
+    // Containers can be registered once, in the registry, for both parsing and validation.
+    let group = ChunkInfo {
+        description: "Vendor group".into(),
+        kind: ChunkKind::Seqt,
+        singleton: false,
+    };
+    registry.register(Some(id("abcd")), id("grp"), group);
+    let report = validate_with_registry(bytes, &registry);
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(report.issues[0].location.offset, 30);
+    let options = ParseOptions::strict().with_registry(&registry);
+    let root = Chunk::from_bytes_with_options(bytes, options).unwrap();
+    assert_eq!(root.select("grp/vndr").unwrap().len(), 2);
+}
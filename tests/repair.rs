@@ -0,0 +1,164 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult,
+    repair::{repair, ChangeKind},
+    riff::MAX_DEPTH,
+    Riff,
+};
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+#[test]
+fn test_intact_files_are_unchanged() -> RiffResult<()> {
+    for path in &[
+        "test_assets/Canimate.avi",
+        "test_assets/Chimes.wav",
+        "test_assets/sample.avi",
+        "test_assets/sample.sf2",
+    ] {
+        let original = std::fs::read(path)?;
+        let mut output = Vec::new();
+        let changes = Riff::from_path(path)?.repair_to(&mut output)?;
+        assert!(changes.is_empty(), "{}: {:?}", path, changes);
+        assert!(output == original, "{}", path);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_crashed_wave() -> RiffResult<()> {
+    let original = std::fs::read("test_assets/Chimes.wav")?;
+    let mut crashed = original.clone();
+    crashed[4..8].copy_from_slice(&[0; 4]);
+    crashed[52..56].copy_from_slice(&[0; 4]);
+    // A chunk the recorder started writing when it crashed.
+    crashed.extend_from_slice(b"LIST\x20");
+
+    let repaired = repair(&crashed)?;
+    let kinds: Vec<_> = repaired
+        .changes
+        .iter()
+        .map(|change| (change.location.offset, change.kind.clone()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (0, ChangeKind::SizeFixed { old: 0, new: 15930 }),
+            (48, ChangeKind::SizeFixed { old: 0, new: 15881 }),
+        ]
+    );
+    assert_eq!(repaired.changes[1].location.path, "RIFF:WAVE/data");
+    // The partial chunk is taken as samples, since nothing tells it apart from them.
+    let bytes = repaired.contents.to_bytes()?;
+    assert!(bytes[56..original.len()] == original[56..]);
+    Ok(())
+}
+
+#[test]
+fn test_truncated_chunk_is_dropped() -> RiffResult<()> {
+    let bytes = b"RIFF\x30\0\0\0smpltest\x02\0\0\0abLIST\x10\0\0\0tst1long\x20\0\0\0ab";
+    let repaired = repair(bytes)?;
+    assert_eq!(
+        repaired.contents.to_bytes()?,
+        b"RIFF\x1a\0\0\0smpltest\x02\0\0\0abLIST\x04\0\0\0tst1"
+    );
+    let kinds: Vec<_> = repaired.changes.iter().map(|c| c.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        [
+            ChangeKind::SizeFixed { old: 48, new: 26 },
+            ChangeKind::Truncated { len: 10 },
+            ChangeKind::SizeFixed { old: 16, new: 4 },
+        ]
+    );
+    assert_eq!(repaired.changes[1].location.offset, 34);
+    assert_eq!(repaired.changes[1].location.path, "RIFF:smpl/LIST:tst1");
+    Ok(())
+}
+
+#[test]
+fn test_crashed_avi() -> RiffResult<()> {
+    let original = std::fs::read("test_assets/Canimate.avi")?;
+    // The recorder crashed before updating `RIFF` and `movi` and writing `idx1`.
+    let mut crashed = original[..91712].to_vec();
+    crashed[4..8].copy_from_slice(&[0; 4]);
+    crashed[4088..4092].copy_from_slice(&[4, 0, 0, 0]);
+
+    let repaired = repair(&crashed)?;
+    let kinds: Vec<_> = repaired.changes.iter().map(|c| c.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        [
+            ChangeKind::SizeFixed { old: 0, new: 91952 },
+            ChangeKind::SizeFixed { old: 4, new: 87620 },
+            ChangeKind::IndexRebuilt { entries: 15 },
+        ]
+    );
+    let bytes = repaired.contents.to_bytes()?;
+    assert_eq!(bytes.len(), original.len());
+    assert!(bytes[..91712] == original[..91712]);
+    // Every entry matches the original index except for its flags.
+    for (rebuilt, original) in bytes[91720..].chunks(16).zip(original[91720..].chunks(16)) {
+        assert_eq!(rebuilt[0..4], original[0..4]);
+        assert_eq!(u32_at(rebuilt, 8), u32_at(original, 8));
+        assert_eq!(u32_at(rebuilt, 12), u32_at(original, 12));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_movi_stops_at_its_parent() -> RiffResult<()> {
+    // `movi` ends with its parent, even though the `JUNK` that follows looks like a movie chunk.
+    let bytes =
+        b"RIFF\x2e\0\0\0AVI LIST\x1a\0\0\0abcdLIST\x0e\0\0\0movi00dc\x02\0\0\0abJUNK\0\0\0\0";
+    let repaired = repair(bytes)?;
+    assert!(repaired.changes.is_empty(), "{:?}", repaired.changes);
+    assert_eq!(repaired.contents.to_bytes()?, bytes);
+    Ok(())
+}
+
+#[test]
+fn test_stale_data_size() -> RiffResult<()> {
+    // The size of `data` was last updated after 4 bytes of samples.
+    let bytes = b"RIFF\x18\0\0\0WAVEdata\x04\0\0\0\x80\x00\x81\x02\xfe\xff\x00\x01\x7f\x10\x00\x00";
+    let repaired = repair(bytes)?;
+    assert_eq!(repaired.changes.len(), 1);
+    assert_eq!(repaired.changes[0].location.offset, 12);
+    assert_eq!(
+        repaired.changes[0].kind,
+        ChangeKind::SizeFixed { old: 4, new: 12 }
+    );
+    let mut expected = bytes.to_vec();
+    expected[16] = 12;
+    assert_eq!(repaired.contents.to_bytes()?, expected);
+    // A `data` chunk followed by another chunk keeps its size.
+    let bytes = b"RIFF\x1a\0\0\0WAVEdata\x04\0\0\0abcdJUNK\x02\0\0\0\0\0";
+    assert!(repair(bytes)?.changes.is_empty());
+    Ok(())
+}
+
+/// Nests `depth` lists around a single chunk, with sizes off by `error` bytes.
+fn nested_lists(depth: usize, error: u32) -> Vec<u8> {
+    let mut bytes = b"abcd\x02\0\0\0xy".to_vec();
+    for _ in 0..depth {
+        let size = (bytes.len() as u32 + 4 - error).to_le_bytes();
+        bytes = [&b"LIST"[..], &size, b"tst1", &bytes].concat();
+    }
+    let size = (bytes.len() as u32 + 4).to_le_bytes();
+    [&b"RIFF"[..], &size, b"smpl", &bytes].concat()
+}
+
+#[test]
+fn test_nested_lists_are_scanned_once() -> RiffResult<()> {
+    let depth = MAX_DEPTH - 1;
+    let intact = nested_lists(depth, 0);
+    assert!(repair(&intact)?.changes.is_empty());
+    let broken = nested_lists(depth, 2);
+    let repaired = repair(&broken)?;
+    assert_eq!(repaired.changes.len(), depth);
+    assert_eq!(repaired.contents.to_bytes()?, intact);
+    Ok(())
+}
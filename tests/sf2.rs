@@ -0,0 +1,207 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult,
+    sf2::{
+        Generator, Info, Instrument, Preset, Sample, SoundFont, Version, Zone, GEN_INSTRUMENT,
+        GEN_SAMPLE_ID, ROM_SAMPLE, SAMPLE_GUARD_LEN,
+    },
+    Chunk, Riff,
+};
+
+#[test]
+fn test_sf2_info() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.sf2")?;
+    let sf2 = SoundFont::from_chunk(&file.as_chunk()?)?;
+    assert_eq!((sf2.info.version.major, sf2.info.version.minor), (2, 1));
+    assert_eq!(sf2.info.sound_engine, "EMU8000");
+    assert_eq!(sf2.info.name, "Test Bank");
+    assert_eq!(sf2.info.comments.as_deref(), Some("generated for tests"));
+    assert_eq!(sf2.info.rom_name, None);
+    Ok(())
+}
+
+#[test]
+fn test_sf2_graph() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.sf2")?;
+    let sf2 = SoundFont::from_chunk(&file.as_chunk()?)?;
+    let names = sf2
+        .presets
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Piano", "Organ"]);
+
+    let piano = &sf2.presets[0];
+    assert_eq!((piano.preset, piano.bank), (0, 0));
+    assert_eq!(piano.zones.len(), 1);
+    assert_eq!(piano.zones[0].modulators.len(), 1);
+    assert_eq!(piano.zones[0].modulators[0].amount, 960);
+
+    let organ = &sf2.presets[1];
+    assert_eq!((organ.preset, organ.bank), (1, 8));
+    assert_eq!(organ.zones.len(), 2);
+    assert_eq!(organ.zones[0].instrument(), None);
+    assert_eq!(organ.zones[0].key_range(), Some((0, 63)));
+
+    let instrument = sf2.instrument(&organ.zones[1]).unwrap();
+    assert_eq!(instrument.name, "Sine");
+    assert_eq!(instrument.zones.len(), 3);
+    assert_eq!(instrument.zones[0].sample(), None);
+    assert_eq!(instrument.zones[0].generators[0].amount_i16(), -100);
+    assert_eq!(instrument.zones[2].key_range(), Some((60, 127)));
+
+    let low = sf2.sample(&instrument.zones[1]).unwrap();
+    let high = sf2.sample(&instrument.zones[2]).unwrap();
+    assert_eq!(low.name, "Sine A");
+    assert_eq!(high.name, "Sine B");
+    Ok(())
+}
+
+#[test]
+fn test_sf2_samples() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.sf2")?;
+    let chunk = file.as_chunk()?;
+    let sf2 = SoundFont::from_chunk(&chunk)?;
+    assert_eq!(sf2.samples.len(), 2);
+
+    let sine_a = &sf2.samples[0];
+    assert_eq!(sine_a.len(), 8);
+    assert_eq!((sine_a.loop_start, sine_a.loop_end), (2, 6));
+    assert_eq!(sine_a.sample_rate, 22050);
+    assert_eq!(sine_a.original_pitch, 60);
+    assert_eq!(sine_a.pitch_correction, -3);
+    assert_eq!(&sine_a.data[2..4], &1000i16.to_le_bytes());
+    assert!(sine_a.data_24.is_none());
+
+    let sine_b = &sf2.samples[1];
+    assert_eq!(sine_b.len(), 4);
+    assert_eq!((sine_b.loop_start, sine_b.loop_end), (0, 4));
+    assert_eq!(&sine_b.data[0..2], &5i16.to_le_bytes());
+
+    // Sample data is borrowed straight from the file.
+    let bytes = chunk.as_bytes().as_ptr_range();
+    assert!(bytes.contains(&sine_b.data.as_ptr()));
+    Ok(())
+}
+
+#[test]
+fn test_non_sf2_form_type() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    assert!(SoundFont::from_chunk(&file.as_chunk()?).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_sf2_round_trip() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.sf2")?;
+    let chunk = file.as_chunk()?;
+    let sf2 = SoundFont::from_chunk(&chunk)?;
+    let mut bytes = Vec::new();
+    sf2.write(&mut bytes)?;
+    assert_eq!(bytes, chunk.as_bytes());
+    assert_eq!(SoundFont::from_chunk(&Chunk::from_bytes(&bytes)?)?, sf2);
+    Ok(())
+}
+
+#[test]
+fn test_sf2_write_from_model() -> RiffResult<()> {
+    let sf2 = SoundFont {
+        info: Info {
+            version: Version { major: 2, minor: 4 },
+            sound_engine: "EMU8000".to_string(),
+            name: "Model".to_string(),
+            software: Some("riffu".to_string()),
+            ..Info::default()
+        },
+        presets: vec![Preset {
+            name: "Lead".to_string(),
+            zones: vec![Zone {
+                generators: vec![Generator {
+                    operator: GEN_INSTRUMENT,
+                    amount: 0,
+                }],
+                modulators: vec![],
+            }],
+            ..Preset::default()
+        }],
+        instruments: vec![Instrument {
+            name: "Square".to_string(),
+            zones: vec![Zone {
+                generators: vec![Generator {
+                    operator: GEN_SAMPLE_ID,
+                    amount: 0,
+                }],
+                modulators: vec![],
+            }],
+        }],
+        samples: vec![Sample {
+            name: "Square".to_string(),
+            loop_start: 0,
+            loop_end: 2,
+            sample_rate: 48000,
+            original_pitch: 69,
+            sample_type: 1,
+            data: vec![0xff, 0x7f, 0x01, 0x80].into(),
+            data_24: Some(vec![0x12, 0x34].into()),
+            ..Sample::default()
+        }],
+    };
+    let mut bytes = Vec::new();
+    sf2.write(&mut bytes)?;
+    let chunk = Chunk::from_bytes(&bytes)?;
+    assert_eq!(SoundFont::from_chunk(&chunk)?, sf2);
+
+    let sdta = chunk.iter()?.nth(1).unwrap()?;
+    let smpl = sdta.iter()?.next().unwrap()?;
+    let sm24 = sdta.iter()?.nth(1).unwrap()?;
+    assert_eq!(smpl.content()?.len(), (2 + SAMPLE_GUARD_LEN) * 2);
+    assert!(smpl.content()?[4..].iter().all(|&b| b == 0));
+    assert_eq!(sm24.content()?.len(), 2 + SAMPLE_GUARD_LEN);
+    Ok(())
+}
+
+#[test]
+fn test_sf2_rom_samples() -> RiffResult<()> {
+    let sf2 = SoundFont {
+        info: Info {
+            version: Version { major: 2, minor: 1 },
+            sound_engine: "EMU8000".to_string(),
+            name: "ROM".to_string(),
+            rom_name: Some("1MGM".to_string()),
+            rom_version: Some(Version { major: 1, minor: 0 }),
+            ..Info::default()
+        },
+        samples: vec![
+            Sample {
+                name: "Flute".to_string(),
+                loop_start: 10,
+                loop_end: 400,
+                sample_type: ROM_SAMPLE | 1,
+                rom_range: Some(100_000..100_500),
+                ..Sample::default()
+            },
+            Sample {
+                name: "Click".to_string(),
+                sample_type: 1,
+                data: vec![0x01, 0x00].into(),
+                ..Sample::default()
+            },
+        ],
+        ..SoundFont::default()
+    };
+    let mut bytes = Vec::new();
+    sf2.write(&mut bytes)?;
+    let chunk = Chunk::from_bytes(&bytes)?;
+    let read = SoundFont::from_chunk(&chunk)?;
+    assert_eq!(read, sf2);
+    assert!(read.samples[0].is_empty());
+
+    // Only the sample held in RAM takes space in `smpl`.
+    let smpl = chunk.select_first("LIST:sdta/smpl")?.unwrap();
+    assert_eq!(smpl.content()?.len(), (1 + SAMPLE_GUARD_LEN) * 2);
+    let shdr = chunk.select_first("LIST:pdta/shdr")?.unwrap().content()?;
+    assert_eq!(&shdr[20..24], &100_000u32.to_le_bytes());
+    assert_eq!(&shdr[28..32], &100_010u32.to_le_bytes());
+    Ok(())
+}
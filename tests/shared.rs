@@ -0,0 +1,91 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult, shared::SharedChunk, walk::Walk, Chunk, ChunkIter, ParseOptions, Riff,
+    RiffError,
+};
+use std::sync::Arc;
+
+#[test]
+fn test_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Riff>();
+    assert_send_sync::<Chunk<'static>>();
+    assert_send_sync::<ChunkIter<'static>>();
+    assert_send_sync::<Walk<'static>>();
+    assert_send_sync::<SharedChunk>();
+}
+
+#[test]
+fn test_shared_chunk() -> RiffResult<()> {
+    let file = Arc::new(Riff::from_path("test_assets/sample.avi")?);
+    let shared = {
+        let root = file.as_chunk()?;
+        let strf = root.select_first("LIST:hdrl/LIST:strl[1]/strf")?.unwrap();
+        SharedChunk::new(file.clone(), &strf)?
+    };
+    let strf = std::thread::spawn(move || shared).join().unwrap();
+    let chunk = strf.chunk();
+    assert_eq!(chunk.offset(), 288);
+    assert_eq!(chunk.payload_len()?, 16);
+    assert_eq!(chunk.path(), "RIFF:AVI /LIST:hdrl/LIST:strl/strf");
+    assert_eq!(chunk.parent().unwrap().offset(), 212);
+    assert_eq!(chunk.siblings()?[0].offset(), 224);
+    assert!(Arc::ptr_eq(strf.riff(), &file));
+
+    let root = SharedChunk::root(file)?;
+    let offsets: Vec<_> = root
+        .children()?
+        .iter()
+        .map(|child| child.chunk().offset())
+        .collect();
+    assert_eq!(offsets, [12, 2024, 2002932]);
+    Ok(())
+}
+
+#[test]
+fn test_shared_chunk_keeps_warnings() -> RiffResult<()> {
+    let file = Riff::from_path_with_options("test_assets/M_busy.ani", ParseOptions::lenient())?;
+    let root = SharedChunk::root(Arc::new(file))?;
+    assert_eq!(root.chunk().warnings().len(), 1);
+    assert_eq!(root.children()?.len(), 5);
+    Ok(())
+}
+
+#[test]
+fn test_foreign_chunk() -> RiffResult<()> {
+    let file = Arc::new(Riff::from_path("test_assets/Chimes.wav")?);
+    let other = Riff::from_path("test_assets/Chimes.wav")?;
+    let chunk = other.as_chunk()?;
+    assert!(matches!(
+        SharedChunk::new(file, &chunk),
+        Err(RiffError::MalformedChunk { .. })
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parallel_iteration() -> RiffResult<()> {
+    use rayon::prelude::*;
+
+    let file = Arc::new(Riff::from_path("test_assets/sample.avi")?);
+    let root = file.as_chunk()?;
+    let offsets: Vec<_> = root
+        .par_iter()?
+        .map(|child| child.unwrap().offset())
+        .collect();
+    assert_eq!(offsets, [12, 2024, 2002932]);
+
+    let walked: Vec<_> = root.walk().map(|entry| entry.unwrap().path).collect();
+    let par_walked: Vec<_> = root.par_walk().map(|entry| entry.unwrap().path).collect();
+    assert_eq!(par_walked, walked);
+
+    let shared = SharedChunk::root(file)?;
+    let lens: Vec<_> = shared
+        .par_children()?
+        .map(|child| child.chunk().payload_len().unwrap())
+        .collect();
+    assert_eq!(lens, [2004, 2000900, 7648]);
+    Ok(())
+}
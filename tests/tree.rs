@@ -0,0 +1,125 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult,
+    tree::{Node, Payload},
+    Riff,
+};
+
+const WELL_FORMED: &[&str] = &[
+    "test_assets/Canimate.avi",
+    "test_assets/Chimes.wav",
+    "test_assets/Spirogra.avi",
+    "test_assets/sample.avi",
+    "test_assets/sample.pal",
+    "test_assets/sample.rmi",
+    "test_assets/animated.webp",
+    "test_assets/sample.sf2",
+    "test_assets/sample.dls",
+    "test_assets/set_3.riff",
+];
+
+#[test]
+fn test_round_trip() -> RiffResult<()> {
+    for path in WELL_FORMED {
+        let node = Node::from_chunk(&Riff::from_path(path)?.as_chunk()?)?;
+        assert_eq!(node.to_bytes()?, std::fs::read(path)?, "{}", path);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_info_strings() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.sf2")?;
+    let chunk = file.as_chunk()?;
+    let name = chunk.select_first("LIST:INFO/INAM")?.unwrap();
+    let ifil = chunk.select_first("LIST:INFO/ifil")?.unwrap();
+    match Node::from_chunk(&name)? {
+        Node::Raw {
+            payload: Payload::Text(text),
+            ..
+        } => assert_eq!(text.len() + 1, name.content()?.len()),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(
+        Node::from_chunk(&ifil)?,
+        Node::Raw {
+            payload: Payload::Bytes(_),
+            ..
+        }
+    ));
+    Ok(())
+}
+
+fn payload(path: &str, query: &str) -> RiffResult<Payload> {
+    let file = Riff::from_path(path)?;
+    let chunk = file.as_chunk()?.select_first(query)?.unwrap();
+    match Node::from_chunk(&chunk)? {
+        Node::Raw { payload, .. } => Ok(payload),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_typed_payloads() -> RiffResult<()> {
+    match payload("test_assets/Chimes.wav", "fmt")? {
+        Payload::Format { format, extension } => {
+            assert_eq!(format.channels, 1);
+            assert!(extension.is_empty());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match payload("test_assets/sample.avi", "LIST:hdrl/avih")? {
+        Payload::MainHeader(header) => assert_eq!(header.streams, 2),
+        other => panic!("unexpected {:?}", other),
+    }
+    match payload("test_assets/extended.webp", "VP8X")? {
+        Payload::Vp8x(vp8x) => assert!(vp8x.canvas_width > 0),
+        other => panic!("unexpected {:?}", other),
+    }
+    // A decoded payload that cannot be written is reported when writing.
+    let mut vp8x = match payload("test_assets/extended.webp", "VP8X")? {
+        Payload::Vp8x(vp8x) => vp8x,
+        other => panic!("unexpected {:?}", other),
+    };
+    vp8x.canvas_width = 0;
+    assert!(Payload::Vp8x(vp8x).to_bytes().is_err());
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() -> RiffResult<()> {
+    for path in WELL_FORMED {
+        let node = Node::from_chunk(&Riff::from_path(path)?.as_chunk()?)?;
+        let json = serde_json::to_string(&node).unwrap();
+        let parsed: Node = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, node);
+        assert_eq!(parsed.to_bytes()?, std::fs::read(path)?, "{}", path);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_layout() {
+    let json = r#"{"kind":"list","id":"RIFF","form_type":"smpl","children":[
+        {"kind":"raw","id":"a\\x00\\x5c ","payload":{"bytes":"00ff"}},
+        {"kind":"seqt","id":"seqt","children":[{"kind":"raw","id":"INAM","payload":{"text":"x"}}]}
+    ]}"#;
+    let node: Node = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        node.to_bytes().unwrap(),
+        &b"RIFF\x20\0\0\0smpla\0\\ \x02\0\0\0\0\xffseqt\x0a\0\0\0INAM\x02\0\0\0x\0"[..]
+    );
+    let again: Node = serde_json::from_str(&serde_json::to_string(&node).unwrap()).unwrap();
+    assert_eq!(again, node);
+    assert!(serde_json::from_str::<Node>(
+        r#"{"kind":"raw","id":"toolong","payload":{"bytes":""}}"#
+    )
+    .is_err());
+    assert!(
+        serde_json::from_str::<Node>(r#"{"kind":"raw","id":"abcd","payload":{"bytes":"0"}}"#)
+            .is_err()
+    );
+}
@@ -0,0 +1,89 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult,
+    validate::{validate, IssueKind},
+    Riff,
+};
+
+#[test]
+fn test_assets_are_valid() -> RiffResult<()> {
+    for path in &[
+        "test_assets/Canimate.avi",
+        "test_assets/Chimes.wav",
+        "test_assets/Spirogra.avi",
+        "test_assets/sample.avi",
+        "test_assets/sample.pal",
+        "test_assets/sample.rmi",
+        "test_assets/animated.webp",
+        "test_assets/sample.sf2",
+        "test_assets/sample.dls",
+    ] {
+        let report = Riff::from_path(path)?.validate();
+        assert!(report.is_valid(), "{}: {}", path, report);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_oversized_riff() -> RiffResult<()> {
+    let report = Riff::from_path("test_assets/M_busy.ani")?.validate();
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(
+        report.issues[0].kind,
+        IssueKind::SizeExceedsParent {
+            declared: 18914,
+            available: 18906,
+        }
+    );
+    assert_eq!(report.issues[0].location.path, "RIFF:ACON");
+    Ok(())
+}
+
+#[test]
+fn test_reports_every_issue() {
+    let mut bytes = b"RIFF\x30\0\0\0xxxx".to_vec();
+    bytes.extend_from_slice(b"LIST\x04\0\0\0empt");
+    bytes.extend_from_slice(b"fmt \x01\0\0\0a\x07");
+    bytes.extend_from_slice(b"fmt \x02\0\0\0ab");
+    bytes.extend_from_slice(b"\x01\x02\x03\x04\x00\0\0\0");
+    bytes.extend_from_slice(b"abcd");
+    bytes.extend_from_slice(b"trailing");
+
+    let report = validate(&bytes);
+    let issues: Vec<_> = report
+        .issues
+        .iter()
+        .map(|issue| (issue.location.offset, issue.kind.clone()))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (0, IssueKind::UnknownFormType { found: *b"xxxx" }),
+            (12, IssueKind::EmptyList),
+            (24, IssueKind::NonZeroPadding { value: 7 }),
+            (34, IssueKind::DuplicateSingleton { id: *b"fmt " }),
+            (
+                44,
+                IssueKind::NonPrintableFourCC {
+                    bytes: [1, 2, 3, 4]
+                }
+            ),
+            (52, IssueKind::TrailingBytes { len: 4 }),
+            (56, IssueKind::TrailingBytes { len: 8 }),
+        ]
+    );
+    assert_eq!(report.issues[1].location.path, "RIFF:xxxx/LIST:empt");
+    assert_eq!(report.issues[5].location.path, "RIFF:xxxx");
+    assert!(report.to_string().lines().count() == 7);
+}
+
+#[test]
+fn test_unparsable_root() {
+    let report = validate(b"RIF");
+    assert_eq!(report.issues.len(), 1);
+    assert!(matches!(
+        report.issues[0].kind,
+        IssueKind::Unparsable { .. }
+    ));
+}
@@ -0,0 +1,171 @@
+extern crate riffu;
+
+use riffu::{
+    carve::scan,
+    diff::diff,
+    error::RiffResult,
+    repair::repair,
+    riff::MAX_DEPTH,
+    tree::Node,
+    validate::{validate, IssueKind},
+    walk::{Entry, Flow, Visitor},
+    Chunk, Riff,
+};
+
+#[test]
+fn test_walk_set_3() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let entries = file
+        .as_chunk()?
+        .walk()
+        .map(|entry| entry.map(|entry| (entry.depth, entry.path, entry.offset)))
+        .collect::<RiffResult<Vec<_>>>()?;
+    let expected = [
+        (0, "RIFF:smpl", 0),
+        (1, "RIFF:smpl/LIST:tst1", 12),
+        (2, "RIFF:smpl/LIST:tst1/test", 24),
+        (2, "RIFF:smpl/LIST:tst1/test", 50),
+        (1, "RIFF:smpl/seqt", 82),
+        (2, "RIFF:smpl/seqt/test", 90),
+    ];
+    assert_eq!(entries.len(), expected.len());
+    for (entry, expected) in entries.iter().zip(&expected) {
+        assert_eq!((entry.0, entry.1.as_str(), entry.2), *expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_walk_skip_children() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/sample.avi")?;
+    let mut walk = file.as_chunk()?.walk();
+    let mut paths = Vec::new();
+    while let Some(entry) = walk.next() {
+        let entry = entry?;
+        if entry.chunk.chunk_type().ok().map(|t| t.into_bytes()) == Some(*b"movi") {
+            walk.skip_children();
+        }
+        paths.push(entry.path);
+    }
+    assert_eq!(paths.len(), 13);
+    assert_eq!(paths[11], "RIFF:AVI /LIST:movi");
+    assert_eq!(paths[12], "RIFF:AVI /idx1");
+    Ok(())
+}
+
+/// Records the callbacks it receives, skipping the containers named `skip` and stopping at the
+/// leaf at offset `stop_at`.
+struct Recorder {
+    events: Vec<String>,
+    skip: &'static str,
+    stop_at: Option<usize>,
+}
+
+impl<'a> Visitor<'a> for Recorder {
+    fn enter_container(&mut self, entry: &Entry<'a>) -> RiffResult<Flow> {
+        self.events.push(format!("enter {}", entry.path));
+        if entry.path.ends_with(self.skip) {
+            return Ok(Flow::SkipChildren);
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn leave_container(&mut self, entry: &Entry<'a>) -> RiffResult<Flow> {
+        self.events.push(format!("leave {}", entry.path));
+        Ok(Flow::Continue)
+    }
+
+    fn visit_leaf(&mut self, entry: &Entry<'a>) -> RiffResult<Flow> {
+        self.events
+            .push(format!("leaf {} {}", entry.depth, entry.path));
+        if Some(entry.offset) == self.stop_at {
+            return Ok(Flow::Stop);
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[test]
+fn test_visitor() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let mut recorder = Recorder {
+        events: Vec::new(),
+        skip: "seqt",
+        stop_at: None,
+    };
+    file.as_chunk()?.visit(&mut recorder)?;
+    assert_eq!(
+        recorder.events,
+        [
+            "enter RIFF:smpl",
+            "enter RIFF:smpl/LIST:tst1",
+            "leaf 2 RIFF:smpl/LIST:tst1/test",
+            "leaf 2 RIFF:smpl/LIST:tst1/test",
+            "leave RIFF:smpl/LIST:tst1",
+            "enter RIFF:smpl/seqt",
+            "leave RIFF:smpl/seqt",
+            "leave RIFF:smpl",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_visitor_stop() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/set_3.riff")?;
+    let mut recorder = Recorder {
+        events: Vec::new(),
+        skip: "none",
+        stop_at: Some(50),
+    };
+    file.as_chunk()?.visit(&mut recorder)?;
+    assert_eq!(recorder.events.len(), 4);
+    assert_eq!(recorder.events[3], "leaf 2 RIFF:smpl/LIST:tst1/test");
+    Ok(())
+}
+
+/// A `RIFF:smpl` file holding `depth` nested lists around an `abcd` chunk.
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut bytes = b"abcd\x02\0\0\0xy".to_vec();
+    for _ in 0..depth {
+        let size = (bytes.len() as u32 + 4).to_le_bytes();
+        bytes = [&b"LIST"[..], &size, b"tst1", &bytes].concat();
+    }
+    let size = (bytes.len() as u32 + 4).to_le_bytes();
+    [&b"RIFF"[..], &size, b"smpl", &bytes].concat()
+}
+
+#[test]
+fn test_walk_deep_nesting() -> RiffResult<()> {
+    let depth = MAX_DEPTH - 1;
+    let bytes = nested_lists(depth);
+    let chunk = Chunk::from_bytes(&bytes)?;
+    let last = chunk.walk().last().unwrap()?;
+    assert_eq!(last.depth, MAX_DEPTH);
+    assert_eq!(
+        last.path.len(),
+        "RIFF:smpl".len() + depth * "/LIST:tst1".len() + 5
+    );
+    assert_eq!(last.chunk.path(), last.path);
+    Ok(())
+}
+
+#[test]
+fn test_nesting_limit() -> RiffResult<()> {
+    let bytes = nested_lists(20_000);
+    let chunk = Chunk::from_bytes(&bytes)?;
+    let err = chunk.walk().find_map(Result::err).unwrap();
+    assert_eq!(err.location().unwrap().offset, 12 * (MAX_DEPTH + 1));
+    assert!(chunk.select("**/abcd").is_err());
+    assert!(Node::from_chunk(&chunk).is_err());
+    assert!(diff(&chunk, &chunk).is_err());
+    let report = validate(&bytes);
+    assert!(report.issues.iter().any(|issue| matches!(
+        &issue.kind,
+        IssueKind::Unparsable { reason } if reason.contains("nested too deeply")
+    )));
+    assert_eq!(scan(&bytes).count(), 0);
+    let repaired = repair(&bytes)?;
+    assert_eq!(repaired.contents.to_bytes()?, bytes);
+    Ok(())
+}
@@ -0,0 +1,167 @@
+extern crate riffu;
+
+use riffu::{
+    error::RiffResult,
+    webp::{rewrite_metadata, Blending, Disposal, Encoding, Metadata, Vp8x, WebP},
+    Chunk, Riff, RiffError,
+};
+
+#[test]
+fn test_lossy_webp() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/lossy.webp")?;
+    let webp = WebP::from_chunk(&file.as_chunk()?)?;
+    assert!(!webp.is_extended());
+    assert!(!webp.is_animated());
+    assert!(!webp.is_lossless());
+    assert_eq!((webp.width(), webp.height()), (16, 8));
+    let bitstream = webp.bitstream.unwrap();
+    assert_eq!(bitstream.encoding, Encoding::Lossy);
+    assert_eq!(bitstream.data.len(), 22);
+    Ok(())
+}
+
+#[test]
+fn test_lossless_webp() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/lossless.webp")?;
+    let webp = WebP::from_chunk(&file.as_chunk()?)?;
+    assert!(webp.is_lossless());
+    assert_eq!((webp.width(), webp.height()), (20, 10));
+    Ok(())
+}
+
+#[test]
+fn test_extended_webp() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/extended.webp")?;
+    let webp = WebP::from_chunk(&file.as_chunk()?)?;
+    let vp8x = webp.vp8x.unwrap();
+    assert!(vp8x.has_icc_profile());
+    assert!(vp8x.has_alpha());
+    assert!(vp8x.has_exif());
+    assert!(vp8x.has_xmp());
+    assert!(!vp8x.has_animation());
+    assert_eq!((webp.width(), webp.height()), (16, 8));
+    assert_eq!(webp.icc_profile, Some(&b"fake icc profile"[..]));
+    assert_eq!(webp.alpha, Some(&b"\x00alpha"[..]));
+    assert_eq!(webp.exif, Some(&b"Exif\0\0II*\0"[..]));
+    assert_eq!(webp.xmp, Some(&b"<x:xmpmeta/>\n"[..]));
+    assert!(webp.bitstream.is_some());
+    Ok(())
+}
+
+#[test]
+fn test_vp8x_canvas_size() -> RiffResult<()> {
+    let vp8x = Vp8x {
+        flags: Vp8x::ALPHA_FLAG,
+        canvas_width: Vp8x::MAX_CANVAS_SIZE,
+        canvas_height: 1,
+    };
+    let bytes = vp8x.to_bytes()?;
+    assert_eq!(bytes, [0x10, 0, 0, 0, 0xff, 0xff, 0xff, 0, 0, 0]);
+    assert_eq!(Vp8x::from_bytes(&bytes)?, vp8x);
+    for (canvas_width, canvas_height) in [(0, 8), (16, Vp8x::MAX_CANVAS_SIZE + 1)] {
+        let vp8x = Vp8x {
+            flags: 0,
+            canvas_width,
+            canvas_height,
+        };
+        assert!(matches!(
+            vp8x.to_bytes(),
+            Err(RiffError::MalformedChunk { .. })
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_animated_webp() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/animated.webp")?;
+    let webp = WebP::from_chunk(&file.as_chunk()?)?;
+    assert!(webp.is_animated());
+    assert!(webp.bitstream.is_none());
+    assert_eq!((webp.width(), webp.height()), (32, 32));
+    let animation = webp.animation.unwrap();
+    assert_eq!(animation.background_color, [0xff, 0x80, 0x00, 0xff]);
+    assert_eq!(animation.loop_count, 3);
+    assert_eq!(webp.frames.len(), 2);
+
+    let first = &webp.frames[0];
+    assert_eq!((first.x_offset, first.y_offset), (0, 0));
+    assert_eq!((first.width, first.height), (16, 16));
+    assert_eq!(first.duration, 100);
+    assert_eq!(first.blending, Blending::AlphaBlend);
+    assert_eq!(first.disposal, Disposal::None);
+    assert!(first.alpha.is_none());
+    assert_eq!(first.bitstream.encoding, Encoding::Lossy);
+
+    let second = &webp.frames[1];
+    assert_eq!((second.x_offset, second.y_offset), (16, 8));
+    assert_eq!((second.width, second.height), (16, 24));
+    assert_eq!(second.duration, 250);
+    assert_eq!(second.blending, Blending::NoBlend);
+    assert_eq!(second.disposal, Disposal::Background);
+    assert_eq!(second.alpha, Some(&b"\x01ab"[..]));
+    assert_eq!(second.bitstream.encoding, Encoding::Lossless);
+    assert_eq!((second.bitstream.width, second.bitstream.height), (16, 24));
+    Ok(())
+}
+
+#[test]
+fn test_non_webp_form_type() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/Chimes.wav")?;
+    assert!(WebP::from_chunk(&file.as_chunk()?).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_webp_rewrite_without_changes() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/extended.webp")?;
+    let chunk = file.as_chunk()?;
+    let metadata = WebP::from_chunk(&chunk)?.metadata();
+    let bytes = rewrite_metadata(&chunk, &metadata)?.to_bytes()?;
+    assert_eq!(bytes, chunk.as_bytes());
+    Ok(())
+}
+
+#[test]
+fn test_webp_inject_metadata_upgrades_to_extended() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/lossy.webp")?;
+    let chunk = file.as_chunk()?;
+    let metadata = Metadata {
+        icc_profile: Some(b"icc"),
+        exif: Some(b"exif"),
+        xmp: Some(b"<xmp/>"),
+    };
+    let bytes = rewrite_metadata(&chunk, &metadata)?.to_bytes()?;
+    let rewritten = Chunk::from_bytes(&bytes)?;
+    let ids = rewritten
+        .iter()?
+        .map(|child| Ok(*child?.id()?.as_bytes()))
+        .collect::<RiffResult<Vec<_>>>()?;
+    assert_eq!(ids, vec![*b"VP8X", *b"ICCP", *b"VP8 ", *b"EXIF", *b"XMP "]);
+
+    let webp = WebP::from_chunk(&rewritten)?;
+    let vp8x = webp.vp8x.unwrap();
+    assert!(vp8x.has_icc_profile() && vp8x.has_exif() && vp8x.has_xmp());
+    assert!(!vp8x.has_alpha() && !vp8x.has_animation());
+    assert_eq!((vp8x.canvas_width, vp8x.canvas_height), (16, 8));
+    assert_eq!(webp.metadata(), metadata);
+    let original = WebP::from_chunk(&chunk)?.bitstream.unwrap();
+    assert_eq!(webp.bitstream.unwrap().data, original.data);
+    Ok(())
+}
+
+#[test]
+fn test_webp_strip_metadata() -> RiffResult<()> {
+    let file = Riff::from_path("test_assets/extended.webp")?;
+    let chunk = file.as_chunk()?;
+    let bytes = rewrite_metadata(&chunk, &Metadata::default())?.to_bytes()?;
+    let rewritten = Chunk::from_bytes(&bytes)?;
+    let webp = WebP::from_chunk(&rewritten)?;
+    let vp8x = webp.vp8x.unwrap();
+    assert!(!vp8x.has_icc_profile() && !vp8x.has_exif() && !vp8x.has_xmp());
+    assert!(vp8x.has_alpha());
+    assert_eq!(webp.metadata(), Metadata::default());
+    assert_eq!(webp.alpha, Some(&b"\x00alpha"[..]));
+    assert_eq!(rewritten.iter()?.count(), 3);
+    Ok(())
+}